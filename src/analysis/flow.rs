@@ -0,0 +1,273 @@
+//! Control flow analysis of function bodies. Every point in a body is either
+//! unreachable, or has a set of locals which have definitely been given a value
+//! on every path leading to it. This is used to find reads of locals which may
+//! not have a value yet, statements which can never be reached, and functions
+//! which may reach their end without returning anything.
+
+use std::collections::HashSet;
+
+use crate::hir;
+use crate::messages::MessageMaker;
+use crate::names::Name;
+use crate::source::Span;
+use crate::types::{Type, TypeNode};
+use crate::Db;
+
+pub fn check(db: &dyn Db, items: hir::Items) {
+    let mut checker = FlowChecker {
+        db,
+        declared: HashSet::new(),
+        state: None,
+        breaks: Vec::new(),
+    };
+
+    checker.check_items(items);
+}
+
+/// The locals which definitely have a value, or `None` if unreachable.
+type State = Option<HashSet<Name>>;
+
+struct FlowChecker<'a> {
+    db: &'a dyn Db,
+
+    /// The locals declared in the blocks of the current function, which don't
+    /// have a value until they are assigned one.
+    declared: HashSet<Name>,
+
+    state: State,
+
+    /// The state at the `break`s out of each enclosing loop.
+    breaks: Vec<State>,
+}
+
+impl FlowChecker<'_> {
+    fn check_items(&mut self, items: hir::Items) {
+        for value in items.values(self.db) {
+            match &value.node {
+                hir::ValueNode::Function {
+                    body: Some(body), ..
+                } => self.check_function(value.span, value.anno, body, &[]),
+
+                hir::ValueNode::Function { body: None, .. } => {}
+
+                hir::ValueNode::Variable { body } => {
+                    if let Some(body) = body {
+                        self.state = Some(HashSet::new());
+                        self.check_expression(body);
+                    }
+                }
+            }
+        }
+
+        for class in items.classes(self.db) {
+            self.check_items(class.items);
+        }
+    }
+
+    /// Check the body of a function or lambda of type `ty`, where the
+    /// `captured` locals of enclosing functions already have a value.
+    fn check_function(&mut self, span: Span, ty: Type, body: &hir::Block, captured: &[Name]) {
+        self.state = Some(captured.iter().copied().collect());
+        self.check_block(body);
+
+        let TypeNode::Function(_, returns) = ty.node(self.db) else {
+            return;
+        };
+
+        let needs_return = !matches!(returns.node(self.db), TypeNode::Unit | TypeNode::Bottom);
+        if needs_return && self.state.is_some() {
+            MessageMaker::at(self.db, span).analysis_missing_return(returns);
+        }
+    }
+
+    fn check_block(&mut self, block: &hir::Block) {
+        for name in block.declared.iter() {
+            self.declared.insert(*name);
+
+            // Locals declared in a loop have no value at the start of every
+            // iteration
+            if let Some(state) = &mut self.state {
+                state.remove(name);
+            }
+        }
+
+        // Only report the first unreachable statement of a block, and nothing
+        // in blocks which are unreachable as a whole
+        let mut reported = self.state.is_none();
+
+        for statement in block.statements.iter() {
+            if self.state.is_none() && !reported {
+                MessageMaker::at(self.db, statement.span).analysis_unreachable_statement();
+                reported = true;
+            }
+
+            self.check_statement(statement);
+        }
+    }
+
+    fn check_statement(&mut self, statement: &hir::Statement) {
+        match &statement.node {
+            hir::StatementNode::Expression(expr) => self.check_expression(expr),
+
+            hir::StatementNode::Assignment(target, body) => {
+                self.check_expression(body);
+
+                match &target.node {
+                    hir::ExpressionNode::Name(name) => {
+                        if let Some(state) = &mut self.state {
+                            state.insert(*name);
+                        }
+                    }
+
+                    _ => self.check_expression(target),
+                }
+            }
+
+            hir::StatementNode::Return(expr) => {
+                self.check_expression(expr);
+                self.state = None;
+            }
+
+            hir::StatementNode::Case(scrutinee, arms, exhaustive) => {
+                self.check_expression(scrutinee);
+
+                let before = self.state.clone();
+                let mut after = if *exhaustive { None } else { before.clone() };
+
+                for arm in arms {
+                    self.state = before.clone();
+                    self.check_block(&arm.body);
+                    after = join(after, self.state.take());
+                }
+
+                self.state = after;
+            }
+
+            hir::StatementNode::If(cond, then, otherwise) => {
+                self.check_expression(cond);
+
+                let before = self.state.clone();
+                self.check_block(then);
+
+                let after = std::mem::replace(&mut self.state, before);
+                if let Some(otherwise) = otherwise {
+                    self.check_block(otherwise);
+                }
+
+                self.state = join(after, self.state.take());
+            }
+
+            // Nothing declared within a loop survives to the next iteration, so
+            // the state at the start of the body is the same every time
+            hir::StatementNode::While(cond, body) => {
+                self.check_expression(cond);
+
+                let before = self.state.clone();
+                self.breaks.push(None);
+                self.check_block(body);
+
+                let breaks = self.breaks.pop().flatten();
+                self.state = join(before, breaks);
+            }
+
+            hir::StatementNode::Loop(body) => {
+                self.breaks.push(None);
+                self.check_block(body);
+                self.state = self.breaks.pop().flatten();
+            }
+
+            hir::StatementNode::Break => {
+                let state = self.state.take();
+                if let Some(breaks) = self.breaks.last_mut() {
+                    *breaks = join(breaks.take(), state);
+                }
+            }
+
+            hir::StatementNode::Continue => self.state = None,
+
+            hir::StatementNode::Null => {}
+        }
+    }
+
+    fn check_expression(&mut self, expr: &hir::Expression) {
+        match &expr.node {
+            hir::ExpressionNode::Name(name) => self.check_read(expr.span, *name),
+
+            hir::ExpressionNode::Reference(expr)
+            | hir::ExpressionNode::Unary(_, expr)
+            | hir::ExpressionNode::Field(expr, _)
+            | hir::ExpressionNode::ToString(expr) => self.check_expression(expr),
+
+            hir::ExpressionNode::Binary(_, lhs, rhs) => {
+                self.check_expression(lhs);
+                self.check_expression(rhs);
+            }
+
+            hir::ExpressionNode::Call(callee, args) => {
+                self.check_expression(callee);
+                for arg in args {
+                    self.check_expression(arg);
+                }
+            }
+
+            hir::ExpressionNode::Construct(_, args) => {
+                for arg in args {
+                    self.check_expression(arg);
+                }
+            }
+
+            hir::ExpressionNode::Interpolated(parts) => {
+                for part in parts {
+                    if let hir::StringPart::Expression(expr) = part {
+                        self.check_expression(expr);
+                    }
+                }
+            }
+
+            // The body of a lambda is checked on its own, but captured locals
+            // must have a value when the lambda is created
+            hir::ExpressionNode::Lambda { body, captures, .. } => {
+                for capture in captures {
+                    self.check_read(expr.span, *capture);
+                }
+
+                let state = self.state.take();
+                let breaks = std::mem::take(&mut self.breaks);
+
+                self.check_function(expr.span, expr.anno, body, captures);
+
+                self.state = state;
+                self.breaks = breaks;
+            }
+
+            hir::ExpressionNode::Number(_)
+            | hir::ExpressionNode::String(_)
+            | hir::ExpressionNode::This
+            | hir::ExpressionNode::Unit
+            | hir::ExpressionNode::Invalid => {}
+        }
+    }
+
+    /// Report an error if the local `name` may not have a value yet.
+    fn check_read(&mut self, span: Span, name: Name) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        if self.declared.contains(&name) && !state.contains(&name) {
+            MessageMaker::at(self.db, span).analysis_uninitialized_local(name.name(self.db));
+
+            // Only report each local once
+            state.insert(name);
+        }
+    }
+}
+
+/// The state at a point reachable from two others.
+fn join(a: State, b: State) -> State {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.intersection(&b).copied().collect()),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
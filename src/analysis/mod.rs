@@ -0,0 +1,25 @@
+//! Checks over the fully annotated [`hir`] which don't affect the types of
+//! anything, but which still reject programs that are wrong.
+
+mod flow;
+mod mutability;
+
+use crate::resolution::resolve_names;
+use crate::source::Source;
+use crate::types::annotate;
+use crate::{hir, Db};
+
+#[cfg(test)]
+mod tests;
+
+/// Check the annotated items of a source, returning them.
+#[salsa::tracked]
+pub fn analyze(db: &dyn Db, source: Source) -> hir::Items {
+    let items = annotate(db, source);
+    let mutable = resolve_names(db, source).mutable(db);
+
+    mutability::check(db, items, &mutable);
+    flow::check(db, items);
+
+    items
+}
@@ -0,0 +1,419 @@
+//! Values are passed around by copying them, so the only way to change a value
+//! owned by someone else is through a reference. Locals may only be assigned to
+//! after they are declared if they are declared with `var`, and the fields of a
+//! value may only be assigned to if the value itself may be. Since `this` is
+//! just another argument, this means methods must take `this &` to change it.
+//!
+//! References must also not outlive the locals they refer to, which is checked
+//! by keeping track of which locals every local might refer to.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::hir;
+use crate::messages::MessageMaker;
+use crate::names::Name;
+use crate::types::TypeNode;
+use crate::Db;
+
+/// The depth of the scope function arguments are declared in. Anything at a
+/// lower depth lives for as long as the program does.
+const ARGUMENTS: usize = 1;
+
+pub fn check(db: &dyn Db, items: hir::Items, mutable: &HashSet<Name>) {
+    let mut checker = MutabilityChecker {
+        db,
+        mutable,
+        depths: HashMap::new(),
+        uninitialized: HashSet::new(),
+        refers: HashMap::new(),
+        depth: ARGUMENTS,
+        base: ARGUMENTS,
+    };
+
+    checker.check_items(items);
+}
+
+/// Something a reference may refer to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Origin {
+    Local(Name),
+    This,
+}
+
+struct MutabilityChecker<'a> {
+    db: &'a dyn Db,
+    mutable: &'a HashSet<Name>,
+
+    /// The depth of the scope each local was declared in.
+    depths: HashMap<Name, usize>,
+
+    /// The locals which are declared, but which have not been given a value.
+    uninitialized: HashSet<Name>,
+
+    /// What the value of each local may refer to.
+    refers: HashMap<Name, Vec<Origin>>,
+
+    depth: usize,
+
+    /// The depth the arguments of the current function or lambda are declared
+    /// at.
+    base: usize,
+}
+
+impl MutabilityChecker<'_> {
+    fn check_items(&mut self, items: hir::Items) {
+        for value in items.values(self.db) {
+            self.check_value(&value);
+        }
+
+        for class in items.classes(self.db) {
+            self.check_items(class.items);
+        }
+    }
+
+    fn check_value(&mut self, value: &hir::Value) {
+        self.depths.clear();
+        self.uninitialized.clear();
+        self.refers.clear();
+
+        match &value.node {
+            hir::ValueNode::Function { args, body, .. } => {
+                for arg in args {
+                    self.depths.insert(*arg, ARGUMENTS);
+                }
+
+                if let Some(body) = body {
+                    self.check_block(body);
+                }
+            }
+
+            hir::ValueNode::Variable { body } => {
+                if let Some(body) = body {
+                    self.check_expression(body);
+                }
+            }
+        }
+    }
+
+    fn check_block(&mut self, block: &hir::Block) {
+        self.depth += 1;
+
+        for name in block.declared.iter() {
+            self.depths.insert(*name, self.depth);
+            self.uninitialized.insert(*name);
+        }
+
+        for statement in block.statements.iter() {
+            self.check_statement(statement);
+        }
+
+        self.depth -= 1;
+    }
+
+    fn check_statement(&mut self, statement: &hir::Statement) {
+        match &statement.node {
+            hir::StatementNode::Expression(expr) => self.check_expression(expr),
+
+            hir::StatementNode::Assignment(target, body) => {
+                self.check_expression(body);
+                self.check_assignment(target, body);
+            }
+
+            hir::StatementNode::Return(expr) => {
+                self.check_expression(expr);
+                for origin in self.refers_to(expr) {
+                    if self.origin_depth(origin) >= self.base {
+                        self.report_dangling(expr, origin);
+                        break;
+                    }
+                }
+            }
+
+            hir::StatementNode::Case(scrutinee, arms, _) => {
+                self.check_expression(scrutinee);
+
+                // The bindings of an arm live for as long as its body
+                self.depth += 1;
+                for arm in arms {
+                    self.declare_bindings(&arm.pattern);
+                    self.check_block(&arm.body);
+                }
+                self.depth -= 1;
+            }
+
+            hir::StatementNode::If(cond, then, otherwise) => {
+                self.check_expression(cond);
+                self.check_block(then);
+                if let Some(otherwise) = otherwise {
+                    self.check_block(otherwise);
+                }
+            }
+
+            hir::StatementNode::While(cond, body) => {
+                self.check_expression(cond);
+                self.check_block(body);
+            }
+
+            hir::StatementNode::Loop(body) => self.check_block(body),
+
+            hir::StatementNode::Break | hir::StatementNode::Continue | hir::StatementNode::Null => {
+            }
+        }
+    }
+
+    fn check_expression(&mut self, expr: &hir::Expression) {
+        match &expr.node {
+            hir::ExpressionNode::Reference(expr)
+            | hir::ExpressionNode::Unary(_, expr)
+            | hir::ExpressionNode::Field(expr, _)
+            | hir::ExpressionNode::ToString(expr) => self.check_expression(expr),
+
+            hir::ExpressionNode::Binary(_, lhs, rhs) => {
+                self.check_expression(lhs);
+                self.check_expression(rhs);
+            }
+
+            hir::ExpressionNode::Call(callee, args) => {
+                self.check_expression(callee);
+                for arg in args {
+                    self.check_expression(arg);
+                }
+            }
+
+            hir::ExpressionNode::Construct(_, args) => {
+                for arg in args {
+                    self.check_expression(arg);
+                }
+            }
+
+            hir::ExpressionNode::Interpolated(parts) => {
+                for part in parts {
+                    if let hir::StringPart::Expression(expr) = part {
+                        self.check_expression(expr);
+                    }
+                }
+            }
+
+            hir::ExpressionNode::Lambda { args, body, .. } => {
+                let base = self.base;
+
+                self.depth += 1;
+                self.base = self.depth;
+
+                for arg in args {
+                    self.depths.insert(*arg, self.depth);
+                }
+
+                self.check_block(body);
+
+                self.depth -= 1;
+                self.base = base;
+            }
+
+            hir::ExpressionNode::Name(_)
+            | hir::ExpressionNode::Number(_)
+            | hir::ExpressionNode::String(_)
+            | hir::ExpressionNode::This
+            | hir::ExpressionNode::Unit
+            | hir::ExpressionNode::Invalid => {}
+        }
+    }
+
+    /// Check that `target` may be assigned the value of `body`, and that it
+    /// doesn't live longer than anything `body` refers to.
+    fn check_assignment(&mut self, target: &hir::Expression, body: &hir::Expression) {
+        let initializes = match &target.node {
+            hir::ExpressionNode::Name(name) => self.uninitialized.remove(name),
+            _ => false,
+        };
+
+        if !initializes {
+            self.check_expression(target);
+            self.check_mutable(target);
+        }
+
+        // Only keep what outlives the target, so as to not report the same
+        // problem again later
+        let depth = self.place_depth(target);
+        let (origins, dangling): (Vec<_>, Vec<_>) = self
+            .refers_to(body)
+            .into_iter()
+            .partition(|origin| self.origin_depth(*origin) <= depth);
+
+        if let Some(origin) = dangling.first() {
+            self.report_dangling(body, *origin);
+        }
+
+        match &target.node {
+            hir::ExpressionNode::Name(name) if self.depths.contains_key(name) => {
+                let refers = self.refers.entry(*name).or_default();
+                for origin in origins {
+                    if !refers.contains(&origin) {
+                        refers.push(origin);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Report an error if the place `target` may not be changed.
+    fn check_mutable(&self, target: &hir::Expression) {
+        match &target.node {
+            hir::ExpressionNode::Name(name)
+                if self.depths.contains_key(name) && !self.mutable.contains(name) =>
+            {
+                MessageMaker::at(self.db, target.span).analysis_immutable_local(name.name(self.db));
+            }
+
+            // Changing a field of something behind a reference is always fine
+            hir::ExpressionNode::Field(of, _) if self.is_reference(of) => {}
+
+            hir::ExpressionNode::Field(of, _) => match &of.node {
+                hir::ExpressionNode::This => {
+                    MessageMaker::at(self.db, target.span).analysis_immutable_this()
+                }
+
+                _ => self.check_mutable(of),
+            },
+
+            // Anything else is either a global or not assignable at all, which
+            // has already been reported
+            _ => {}
+        }
+    }
+
+    /// Declare the bindings of a pattern in the current scope.
+    fn declare_bindings(&mut self, pattern: &hir::Pattern) {
+        match &pattern.node {
+            hir::PatternNode::Binding(name) => {
+                self.depths.insert(*name, self.depth);
+            }
+
+            hir::PatternNode::Class(_, fields) => {
+                for field in fields {
+                    self.declare_bindings(field);
+                }
+            }
+
+            hir::PatternNode::Invalid => {}
+        }
+    }
+
+    /// The locals the value of `expr` may refer to.
+    fn refers_to(&self, expr: &hir::Expression) -> Vec<Origin> {
+        if !self.may_refer(expr) {
+            return Vec::new();
+        }
+
+        let mut origins = Vec::new();
+        let mut add = |new: Vec<Origin>| {
+            for origin in new {
+                if !origins.contains(&origin) {
+                    origins.push(origin);
+                }
+            }
+        };
+
+        match &expr.node {
+            hir::ExpressionNode::Reference(place) => add(self.place_origins(place)),
+            hir::ExpressionNode::Name(name) => {
+                add(self.refers.get(name).cloned().unwrap_or_default())
+            }
+
+            hir::ExpressionNode::Field(of, _) => add(self.refers_to(of)),
+
+            // The result of a call may refer to anything its arguments do
+            hir::ExpressionNode::Call(callee, args) => {
+                add(self.refers_to(callee));
+                for arg in args {
+                    add(self.refers_to(arg));
+                }
+            }
+
+            hir::ExpressionNode::Construct(_, args) => {
+                for arg in args {
+                    add(self.refers_to(arg));
+                }
+            }
+
+            hir::ExpressionNode::Lambda { captures, .. } => {
+                for capture in captures {
+                    add(self.refers.get(capture).cloned().unwrap_or_default());
+                }
+            }
+
+            _ => {}
+        }
+
+        origins
+    }
+
+    /// The locals a reference to the place `expr` refers to.
+    fn place_origins(&self, expr: &hir::Expression) -> Vec<Origin> {
+        match &expr.node {
+            hir::ExpressionNode::Name(name) if self.depths.contains_key(name) => {
+                vec![Origin::Local(*name)]
+            }
+
+            hir::ExpressionNode::This => vec![Origin::This],
+
+            hir::ExpressionNode::Field(of, _) if self.is_reference(of) => self.refers_to(of),
+            hir::ExpressionNode::Field(of, _) => self.place_origins(of),
+
+            // Globals live forever, and references to temporaries are only
+            // usable for as long as they are
+            _ => Vec::new(),
+        }
+    }
+
+    /// The depth of the scope the place `expr` lives in.
+    fn place_depth(&self, expr: &hir::Expression) -> usize {
+        match &expr.node {
+            hir::ExpressionNode::Name(name) => self.depths.get(name).copied().unwrap_or(0),
+            hir::ExpressionNode::This => ARGUMENTS,
+
+            // Whatever is behind the reference lives at least as long as the
+            // longest-lived thing it may refer to
+            hir::ExpressionNode::Field(of, _) if self.is_reference(of) => self
+                .refers_to(of)
+                .into_iter()
+                .map(|origin| self.origin_depth(origin))
+                .min()
+                .unwrap_or(0),
+
+            hir::ExpressionNode::Field(of, _) => self.place_depth(of),
+
+            _ => self.depth,
+        }
+    }
+
+    fn origin_depth(&self, origin: Origin) -> usize {
+        match origin {
+            Origin::Local(name) => self.depths.get(&name).copied().unwrap_or(0),
+            Origin::This => ARGUMENTS,
+        }
+    }
+
+    fn report_dangling(&self, at: &hir::Expression, origin: Origin) {
+        let name = match origin {
+            Origin::Local(name) => Some(name.name(self.db)),
+            Origin::This => None,
+        };
+
+        MessageMaker::at(self.db, at.span).analysis_dangling_reference(name);
+    }
+
+    fn is_reference(&self, expr: &hir::Expression) -> bool {
+        matches!(expr.anno.node(self.db), TypeNode::Reference(_))
+    }
+
+    /// Can the value of `expr` contain a reference?
+    fn may_refer(&self, expr: &hir::Expression) -> bool {
+        !matches!(
+            expr.anno.node(self.db),
+            TypeNode::Bottom | TypeNode::Unit | TypeNode::Int | TypeNode::Nat | TypeNode::Boolean
+        )
+    }
+}
@@ -0,0 +1,160 @@
+use crate::source::{Lints, Source};
+use crate::{Db, Messages};
+
+use super::analyze;
+
+#[derive(Default)]
+#[salsa::db(crate::Jar)]
+struct Database {
+    storage: salsa::Storage<Self>,
+}
+
+impl salsa::Database for Database {}
+
+fn check(db: &dyn Db, text: &str) -> Vec<String> {
+    let source = Source::new(db, text.into(), String::new(), Lints::NONE);
+    analyze(db, source);
+    analyze::accumulated::<Messages>(db, source)
+        .into_iter()
+        .filter_map(|message| message.code)
+        .collect()
+}
+
+#[test]
+fn check_mutability() {
+    let db = Database::default();
+    let text = "\
+class Counter
+    var count Int
+
+    function bump(this &) Unit
+        this.count := this.count + 1
+    end
+
+    function reset(this) Unit
+        this.count := 0
+    end
+end
+
+function main(start Int) Unit
+    var total Int := start
+    total := total + 1
+
+    let fixed Int := 5
+    fixed := 6
+
+    let counter Counter := Counter(0)
+    counter.count := 1
+
+    let reference &Counter := counter&
+    reference.count := 2
+end
+";
+
+    assert_eq!(check(&db, text), ["EA00", "EA00", "EA01"].map(String::from));
+}
+
+#[test]
+fn check_dangling_references() {
+    let db = Database::default();
+    let text = "\
+function escape() &Int
+    let x Int := 5
+    return x&
+end
+
+function outlive(cond Boolean) Unit
+    var outer &Int := escape()
+    if cond
+        let inner Int := 1
+        outer := inner&
+    end
+
+    let fine &Int := outer
+end
+
+function forward(x &Int) &Int
+    return x
+end
+";
+
+    assert_eq!(check(&db, text), ["EA02", "EA02"].map(String::from));
+}
+
+#[test]
+fn check_control_flow() {
+    let db = Database::default();
+    let text = "\
+variant Shape
+    class Circle end
+    class Square end
+end
+
+function sign(x Int) Int
+    if x < 0
+        return 0 - 1
+    else
+        return 1
+    end
+end
+
+function missing(x Int) Int
+    if x < 0
+        return 0
+    end
+end
+
+function unreachable() Int
+    return 1
+    return 2
+end
+
+function shape(shape Shape) Int
+    case shape
+        is Shape.Circle return 1 end
+        is Shape.Square return 2 end
+    end
+end
+
+function forever() Int
+    loop
+        null
+    end
+end
+
+function escapes() Int
+    loop
+        break
+    end
+end
+
+function circular() Int
+    let x Int := x + 1
+    return x
+end
+";
+
+    assert_eq!(
+        check(&db, text),
+        ["EA10", "WA10", "EA10", "EA11"].map(String::from)
+    );
+}
+
+#[test]
+fn check_lambda_captures() {
+    let db = Database::default();
+    let text = "\
+function captures(x Int) Int
+    let y Int := x
+    let f () Int := function() Int
+        let z Int := y + z
+        return z
+    end
+
+    return f()
+end
+";
+
+    // Only the local of the lambda itself may be read without a value
+    assert_eq!(check(&db, text), ["EA11"].map(String::from));
+}
@@ -1,10 +1,56 @@
-use crate::names::NamePart;
+use crate::cst::{NodeId, NodeKind, SyntaxTree};
+use crate::names::{NameNode, NamePart};
 use crate::source::Span;
+use crate::Db;
 
 #[salsa::tracked]
 pub struct Declarations {
+    #[return_ref]
+    pub imports: Vec<Import>,
+
     #[return_ref]
     pub declarations: Vec<Declaration>,
+
+    /// The lossless syntax tree of the entire source.
+    #[return_ref]
+    pub syntax: SyntaxTree,
+}
+
+impl Declarations {
+    /// The declaration parsed from the given syntax node, or else from its
+    /// closest ancestor which is a declaration.
+    pub fn enclosing(&self, db: &dyn Db, node: NodeId) -> Option<Declaration> {
+        let syntax = self.syntax(db);
+
+        syntax
+            .ancestors(node)
+            .filter(|id| syntax.node(*id).kind == NodeKind::Declaration)
+            .find_map(|id| self.declaration(db, id))
+    }
+
+    /// The declaration parsed from exactly the given syntax node, which may be
+    /// nested within a class or variant.
+    pub fn declaration(&self, db: &dyn Db, node: NodeId) -> Option<Declaration> {
+        fn find(db: &dyn Db, declarations: &[Declaration], node: NodeId) -> Option<Declaration> {
+            declarations.iter().find_map(|declaration| {
+                if declaration.syntax(db) == node {
+                    return Some(*declaration);
+                }
+
+                match declaration.node(db) {
+                    DeclarationNode::Class {
+                        public, private, ..
+                    }
+                    | DeclarationNode::Variant {
+                        public, private, ..
+                    } => find(db, public, node).or_else(|| find(db, private, node)),
+                    _ => None,
+                }
+            })
+        }
+
+        find(db, self.declarations(db), node)
+    }
 }
 
 #[salsa::tracked]
@@ -14,29 +60,56 @@ pub struct Declaration {
     #[return_ref]
     pub node: DeclarationNode,
     pub span: Span,
+
+    /// The doc comments directly preceding this declaration, one per line.
+    #[return_ref]
+    pub docs: Vec<String>,
+
+    /// The node in the syntax tree of the enclosing [`Declarations`] this
+    /// declaration was parsed from.
+    pub syntax: NodeId,
+}
+
+/// An import of another module of the project, like `import util.strings`,
+/// which makes its top-level names visible in the importing source.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Import {
+    /// The path of the imported module, with one name per directory.
+    pub module: Vec<NamePart>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum DeclarationNode {
     Class {
+        type_params: Vec<TypeParameter>,
         public: Vec<Declaration>,
         private: Vec<Declaration>,
         inherits: Vec<Type>,
     },
 
     Variant {
+        type_params: Vec<TypeParameter>,
         public: Vec<Declaration>,
         private: Vec<Declaration>,
         inherits: Vec<Type>,
     },
 
     Function {
+        type_params: Vec<TypeParameter>,
+
         /// `None` if the function does not take a `this` argument; `Some(n)` if
         /// it does, where `n` is the number of references it is behind.
         this: Option<usize>,
-        args: Vec<(NamePart, Type)>,
+
+        /// The arguments of the function, along with where their names are.
+        args: Vec<(NamePart, Span, Type)>,
         return_type: Type,
         body: Option<Block>,
+
+        /// Is the function marked `@runtime`? Such functions have no body, but
+        /// are provided by the runtime rather than being abstract.
+        runtime: bool,
     },
 
     Variable {
@@ -45,6 +118,31 @@ pub enum DeclarationNode {
     },
 }
 
+/// A type parameter of a generic class, variant or function, like the `T` in
+/// `class List[T]`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TypeParameter {
+    pub variance: Variance,
+    pub name: NamePart,
+    pub span: Span,
+}
+
+/// How subtyping between the arguments of an applied type relates to
+/// subtyping between the applied types themselves.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Variance {
+    /// `List(A)` is only a subtype of `List(B)` if `A` and `B` are the same.
+    Invariant,
+
+    /// Written `out T` or `+T`; `List(A)` is a subtype of `List(B)` if `A` is a
+    /// subtype of `B`.
+    Covariant,
+
+    /// Written `in T` or `-T`; `List(A)` is a subtype of `List(B)` if `B` is a
+    /// subtype of `A`.
+    Contravariant,
+}
+
 /// A declaration name is possibly a prefix (the name of the inherited class)
 /// plus a function name. The function name may be quoted (in which case it
 /// refers to a builtin, like an operator).
@@ -66,6 +164,11 @@ pub enum DeclarationNameNode {
 pub struct Type {
     pub node: TypeNode,
     pub span: Span,
+
+    /// The syntax node this was parsed from. `None` if the parser filled it in
+    /// itself, or if it is part of an interpolation, whose code is inside a
+    /// single string token.
+    pub syntax: Option<NodeId>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -96,6 +199,9 @@ pub struct Block(pub Vec<Statement>);
 pub struct Statement {
     pub node: StatementNode,
     pub span: Span,
+
+    /// The syntax node this was parsed from; see [`Type::syntax`].
+    pub syntax: Option<NodeId>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -109,28 +215,205 @@ pub enum StatementNode {
 
     Return(Expression),
 
+    /// Match the value of an expression against a series of patterns, running
+    /// the body of the first arm that matches.
+    Case(Expression, Vec<CaseArm>),
+
+    /// An `if` with an optional `else` block. A chain of `else if`s is
+    /// represented as an `else` block containing a single `if` statement.
+    If(Expression, Block, Option<Block>),
+    While(Expression, Block),
+    Loop(Block),
+
+    Break,
+    Continue,
+
     Null,
     Invalid,
 }
 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CaseArm {
+    pub pattern: Pattern,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Pattern {
+    pub node: PatternNode,
+    pub span: Span,
+
+    /// The syntax node this was parsed from; see [`Type::syntax`].
+    pub syntax: Option<NodeId>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PatternNode {
+    /// Bind the matched value to a new local.
+    Binding(NamePart),
+
+    /// Match a (possibly nested) class like `A.B`, and match its fields
+    /// positionally against the given patterns.
+    Class(Type, Vec<Pattern>),
+
+    Invalid,
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Expression {
     pub node: ExpressionNode,
     pub span: Span,
+
+    /// The syntax node this was parsed from; see [`Type::syntax`].
+    pub syntax: Option<NodeId>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ExpressionNode {
     Reference(Box<Expression>),
 
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+
     Call(Box<Expression>, Vec<Expression>),
     Field(Box<Expression>, NamePart),
 
     Name(NamePart),
-    Number(String),
+    Number(NumberLiteral),
     String(String),
     This,
     Unit,
 
+    /// A string with interpolated expressions, like `"var {name}"`. Each
+    /// expression is converted with `to_string` from `Stringable`, and the
+    /// parts are then concatenated in order.
+    Interpolated(Vec<StringPart>),
+    Lambda(Lambda),
+
     Invalid,
 }
+
+/// An anonymous function, like `function(x Int) Int return x end`. Parameters
+/// and return types which are left out are inferred from the type the lambda is
+/// expected to have.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Lambda {
+    pub params: Vec<(NamePart, Span, Option<Type>)>,
+    pub return_type: Option<Type>,
+    pub body: Block,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct NumberLiteral {
+    pub value: NumberValue,
+    pub suffix: Option<NumberSuffix>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NumberValue {
+    Integer(u128),
+
+    /// The number `mantissa * 10^exponent`, where `exponent` is negative and
+    /// `mantissa` is not divisible by ten.
+    Fraction {
+        mantissa: u128,
+        exponent: i32,
+    },
+}
+
+/// An explicit type for a number literal, like `5nat`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NumberSuffix {
+    Int,
+    Nat,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Expression(Expression),
+}
+
+/// Binary operators are sugar for calling the quoted function of the same name
+/// on the left operand, such that `a + b` calls `"+"` with `a` as `this` and `b`
+/// as its only argument.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BinaryOperator {
+    Less,
+    Equal,
+    Greater,
+
+    Add,
+    Subtract,
+
+    Multiply,
+    Divide,
+}
+
+impl BinaryOperator {
+    pub const ALL: [Self; 7] = [
+        Self::Less,
+        Self::Equal,
+        Self::Greater,
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+    ];
+
+    /// The quoted name of the function this operator calls.
+    pub fn name(&self, db: &dyn Db) -> NamePart {
+        NamePart::new(db, NameNode::Quoted(self.function_name().into()))
+    }
+
+    /// The name of the quoted function this operator calls.
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::Equal => "=",
+            Self::Greater => ">",
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, Self::Less | Self::Equal | Self::Greater)
+    }
+}
+
+/// Like binary operators, unary operators call a quoted function on their
+/// operand. Negation calls `"neg"` rather than `"-"`, so that a class can
+/// define both subtraction and negation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnaryOperator {
+    Negate,
+}
+
+impl UnaryOperator {
+    pub const ALL: [Self; 1] = [Self::Negate];
+
+    /// The quoted name of the function this operator calls.
+    pub fn name(&self, db: &dyn Db) -> NamePart {
+        NamePart::new(db, NameNode::Quoted(self.function_name().into()))
+    }
+
+    /// The name of the quoted function this operator calls.
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Self::Negate => "neg",
+        }
+    }
+}
+
+/// Is `name` the quoted name of any operator function?
+pub fn is_operator_name(name: &str) -> bool {
+    BinaryOperator::ALL
+        .iter()
+        .map(BinaryOperator::function_name)
+        .chain(UnaryOperator::ALL.iter().map(UnaryOperator::function_name))
+        .any(|op| op == name)
+}
@@ -0,0 +1,264 @@
+//! The concrete syntax tree of a source file. Unlike the [`ast`](crate::ast),
+//! it keeps every token of the source, including whitespace and comments, such
+//! that the source text can be reproduced from it exactly.
+//!
+//! The parser builds the syntax tree alongside the abstract syntax tree, and
+//! every declaration, statement, expression, pattern and type refers back to
+//! the syntax node it was parsed from. Going the other way,
+//! [`Declarations::enclosing`](crate::ast::Declarations::enclosing) finds the
+//! declaration a syntax node belongs to.
+
+use crate::source::{Source, Span};
+use crate::token::Token;
+use crate::Db;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub(crate) fn new(index: usize) -> Self {
+        Self(index)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NodeKind {
+    File,
+    Import,
+    Declaration,
+    Block,
+    Statement,
+    Expression,
+    Pattern,
+    Type,
+
+    /// Tokens skipped while recovering from a syntax error.
+    Error,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SyntaxTree {
+    nodes: Vec<SyntaxNode>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub span: Span,
+    pub parent: Option<NodeId>,
+    pub children: Vec<SyntaxElement>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SyntaxElement {
+    Node(NodeId),
+    Token(Token, Span),
+}
+
+impl SyntaxTree {
+    /// The node spanning the entire source file.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn node(&self, id: NodeId) -> &SyntaxNode {
+        &self.nodes[id.0]
+    }
+
+    /// The given node followed by each of its ancestors, up to the root.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(id), |id| self.node(*id).parent)
+    }
+
+    /// The innermost node containing the given offset of the source.
+    pub fn node_at(&self, offset: usize) -> NodeId {
+        let mut id = self.root();
+
+        'descend: loop {
+            for child in self.node(id).children.iter() {
+                let SyntaxElement::Node(child) = child else {
+                    continue;
+                };
+
+                let span = self.node(*child).span;
+                if (span.start..span.end).contains(&offset) {
+                    id = *child;
+                    continue 'descend;
+                }
+            }
+
+            return id;
+        }
+    }
+
+    /// Every token within the given node, in source order.
+    pub fn tokens(&self, id: NodeId) -> Vec<(&Token, Span)> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(id, &mut tokens);
+        tokens
+    }
+
+    /// Reproduce the source text of the given node from its tokens.
+    pub fn text(&self, db: &dyn Db, id: NodeId) -> String {
+        let node = self.node(id);
+        let text = node.span.source.text(db);
+
+        self.tokens(id)
+            .into_iter()
+            .map(|(_, span)| &text[span.start..span.end])
+            .collect()
+    }
+
+    /// Build the syntax tree from every token of a source and the events the
+    /// parser produced while parsing it. Any trivia before a node is placed in
+    /// its parent.
+    pub(crate) fn build(source: Source, tokens: &[(Token, Span)], events: &[Event]) -> Self {
+        let count = events
+            .iter()
+            .filter(|event| matches!(event, Event::Start(..)))
+            .count();
+
+        let root = SyntaxNode {
+            kind: NodeKind::File,
+            span: Span::new(source, 0, 0),
+            parent: None,
+            children: Vec::new(),
+        };
+
+        let mut nodes = vec![None; count + 1];
+        nodes[0] = Some(root);
+
+        let mut builder = Builder {
+            nodes,
+            stack: vec![NodeId(0)],
+            tokens,
+            source,
+            offset: 0,
+        };
+
+        for event in events {
+            match event {
+                Event::Start(kind, id) => {
+                    builder.trivia();
+                    builder.start(*kind, *id);
+                }
+
+                Event::Token => {
+                    builder.trivia();
+                    builder.token();
+                }
+
+                Event::Finish => builder.finish(),
+            }
+        }
+
+        while !builder.tokens.is_empty() {
+            builder.token();
+        }
+
+        builder.finish();
+
+        Self {
+            nodes: builder
+                .nodes
+                .into_iter()
+                .map(|node| node.expect("every syntax node is started"))
+                .collect(),
+        }
+    }
+
+    fn collect_tokens<'a>(&'a self, id: NodeId, tokens: &mut Vec<(&'a Token, Span)>) {
+        for child in self.node(id).children.iter() {
+            match child {
+                SyntaxElement::Node(id) => self.collect_tokens(*id, tokens),
+                SyntaxElement::Token(token, span) => tokens.push((token, *span)),
+            }
+        }
+    }
+}
+
+/// Something that happened while parsing, in the order it happened. Nodes are
+/// numbered as they are started by the parser, which isn't necessarily the
+/// order they begin in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Event {
+    Start(NodeKind, NodeId),
+    Token,
+    Finish,
+}
+
+struct Builder<'a> {
+    nodes: Vec<Option<SyntaxNode>>,
+    stack: Vec<NodeId>,
+    tokens: &'a [(Token, Span)],
+    source: Source,
+
+    /// The end of the last token added to the tree.
+    offset: usize,
+}
+
+impl Builder<'_> {
+    fn node(&self, id: NodeId) -> &SyntaxNode {
+        self.nodes[id.0].as_ref().expect("syntax node not started")
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut SyntaxNode {
+        self.nodes[id.0].as_mut().expect("syntax node not started")
+    }
+
+    fn current(&mut self) -> &mut SyntaxNode {
+        let id = *self.stack.last().expect("unbalanced syntax tree events");
+        self.node_mut(id)
+    }
+
+    fn start(&mut self, kind: NodeKind, id: NodeId) {
+        let parent = self.stack.last().copied();
+
+        self.current().children.push(SyntaxElement::Node(id));
+        self.nodes[id.0] = Some(SyntaxNode {
+            kind,
+            span: Span::new(self.source, self.offset, self.offset),
+            parent,
+            children: Vec::new(),
+        });
+
+        self.stack.push(id);
+    }
+
+    fn finish(&mut self) {
+        let id = self.stack.pop().expect("unbalanced syntax tree events");
+        let node = self.node(id);
+
+        let span_of = |element: &SyntaxElement| match element {
+            SyntaxElement::Node(id) => self.node(*id).span,
+            SyntaxElement::Token(_, span) => *span,
+        };
+
+        let span = match (node.children.first(), node.children.last()) {
+            (Some(first), Some(last)) => span_of(first) + span_of(last),
+            _ => Span::new(self.source, self.offset, self.offset),
+        };
+
+        self.node_mut(id).span = span;
+    }
+
+    fn token(&mut self) {
+        if let Some(((token, span), rest)) = self.tokens.split_first() {
+            self.tokens = rest;
+            self.offset = span.end;
+            self.current()
+                .children
+                .push(SyntaxElement::Token(token.clone(), *span));
+        }
+    }
+
+    fn trivia(&mut self) {
+        while let Some((token, _)) = self.tokens.first() {
+            if !token.is_trivia() {
+                break;
+            }
+
+            self.token();
+        }
+    }
+}
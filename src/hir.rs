@@ -1,3 +1,7 @@
+//! The fully type checked program, where every expression is annotated with
+//! its type.
+
+use crate::ast::{BinaryOperator, NumberLiteral, UnaryOperator};
 use crate::names::Name;
 use crate::source::Span;
 use crate::types::Type;
@@ -26,6 +30,10 @@ pub struct Value {
     pub name: Name,
     pub node: ValueNode,
     pub span: Span,
+
+    /// The type of the value. For functions this does not include the `this`
+    /// argument.
+    pub anno: Type,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -58,11 +66,48 @@ pub struct Statement {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum StatementNode {
     Expression(Expression),
-    Assignment(Name, Expression),
+    Assignment(Expression, Expression),
     Return(Expression),
+
+    /// Match a value against some patterns, along with whether the arms cover
+    /// every possible value.
+    Case(Expression, Vec<CaseArm>, bool),
+
+    If(Expression, Block, Option<Block>),
+    While(Expression, Block),
+    Loop(Block),
+
+    Break,
+    Continue,
+
     Null,
 }
 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CaseArm {
+    pub pattern: Pattern,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Pattern {
+    pub node: PatternNode,
+    pub span: Span,
+    pub anno: Type,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PatternNode {
+    Binding(Name),
+
+    /// Matches instances of the given class, with patterns for its fields in
+    /// the order they were declared in.
+    Class(Name, Vec<Pattern>),
+
+    Invalid,
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Expression {
     pub node: ExpressionNode,
@@ -74,14 +119,46 @@ pub struct Expression {
 pub enum ExpressionNode {
     Reference(Box<Expression>),
 
+    /// An operator on builtin types. Operators on classes are turned into
+    /// calls to their operator functions.
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+
     Call(Box<Expression>, Vec<Expression>),
     Field(Box<Expression>, Name),
 
+    /// Create an instance of a class, giving the values of its fields in the
+    /// order they were declared in.
+    Construct(Name, Vec<Expression>),
+
     Name(Name),
-    Number(String),
+    Number(NumberLiteral),
     String(String),
+
+    /// A string with interpolated expressions, each of which is converted with
+    /// a call to `to_string` or [`ExpressionNode::ToString`] unless it could
+    /// not be checked.
+    Interpolated(Vec<StringPart>),
+
+    /// Convert a value of a builtin type like `Int` to a string, which the
+    /// runtime does in place of calling `to_string`.
+    ToString(Box<Expression>),
     This,
     Unit,
 
+    Lambda {
+        args: Vec<Name>,
+        body: Block,
+
+        /// The locals of enclosing functions this lambda refers to.
+        captures: Vec<Name>,
+    },
+
     Invalid,
 }
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Expression(Expression),
+}
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::names::Name;
-use crate::resolution::resolve_names;
+use crate::resolution::visible_classes;
 use crate::rst::{Class, ClassKind, Type, TypeNode};
 use crate::source::Source;
 use crate::Db;
@@ -16,12 +16,10 @@ pub struct Mentions {
 
 #[salsa::tracked]
 pub fn all_mentions(db: &dyn Db, source: Source) -> Mentions {
-    let tree = resolve_names(db, source);
-    let items = tree.tree(db);
-
+    let classes = visible_classes(db, source);
     let mut mentioner = MentionLocator::new();
-    for (name, item) in items.classes(db) {
-        mentioner.class_mentions(name, item);
+    for (name, item) in classes {
+        mentioner.class_mentions(classes, name, item);
     }
 
     Mentions::new(db, mentioner.inherits)
@@ -39,12 +37,16 @@ impl MentionLocator {
         }
     }
 
-    pub fn class_mentions(&mut self, name: &Name, item: &Class) {
+    pub fn class_mentions(&mut self, classes: &HashMap<Name, Class>, name: &Name, item: &Class) {
         let mut set = HashSet::new();
         for ty in &item.inherits {
             Self::type_mentions(&mut set, ty);
         }
 
+        // Only classes take part in inheritance; type parameters and the like
+        // are not interesting here.
+        set.retain(|name| classes.contains_key(name));
+
         // Nested items also inherit from outer variants
         if let ClassKind::Variant = item.kind {
             for nested in item.fields.classes.iter() {
@@ -1,7 +1,9 @@
 use messages::Message;
 use salsa::DbWithJar;
 
+pub mod analysis;
 pub mod ast;
+pub mod cst;
 pub mod hir;
 pub mod inheritance;
 pub mod messages;
@@ -17,6 +19,7 @@ mod components;
 
 #[salsa::jar(db = Db)]
 pub struct Jar(
+    crate::analysis::analyze,
     crate::ast::Declaration,
     crate::ast::Declarations,
     crate::hir::Items,
@@ -27,13 +30,20 @@ pub struct Jar(
     crate::names::Name,
     crate::parse::parse,
     crate::resolution::all_names_within,
+    crate::resolution::resolve_imports,
+    crate::resolution::visible_classes,
+    crate::resolution::visible_names,
     crate::resolution::resolve_names,
+    crate::resolution::Imports,
     crate::resolution::NameInfo,
     crate::resolution::NamesWithin,
     crate::rst::Items,
+    crate::source::Project,
     crate::source::Source,
     crate::token::lex,
     crate::types::annotate,
+    crate::types::member_table,
+    crate::types::MemberTable,
     crate::types::type_info,
     crate::types::Type,
     crate::types::TypeInfo,
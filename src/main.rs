@@ -5,7 +5,14 @@ use salsa::{Snapshot, Storage};
 
 fn main() {
     let db = Database::default();
-    let source = source::Source::new(&db, include_str!("../test.rry").into(), "main.rry".into());
+    let source = source::Source::new(
+        &db,
+        include_str!("../test.rry").into(),
+        "main.rry".into(),
+        source::Lints::default(),
+    );
+
+    source::Project::new(&db, vec![source], source::prelude(&db));
 
     let info = types::type_info(&db, source);
     let subtypes = info.subtypes(&db);
@@ -0,0 +1,95 @@
+use super::{part_name, Label, Message, MessageMaker};
+use crate::names::NamePart;
+use crate::types::{pretty_type, Type};
+
+const IMMUTABLE_LOCAL: &str = "EA00";
+const IMMUTABLE_THIS: &str = "EA01";
+const DANGLING_REFERENCE: &str = "EA02";
+const MISSING_RETURN: &str = "EA10";
+const UNINITIALIZED_LOCAL: &str = "EA11";
+const UNREACHABLE_STATEMENT: &str = "WA10";
+
+impl MessageMaker<'_> {
+    pub fn analysis_immutable_local(&self, name: NamePart) {
+        let name = part_name(self.db, name);
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("'{name}' is not declared with 'var'"))];
+
+        self.add(
+            Message::error()
+                .with_code(IMMUTABLE_LOCAL)
+                .with_message(format!("cannot assign to immutable local '{name}'"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn analysis_immutable_this(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("this function takes 'this' by value, so changes to it are lost")];
+
+        self.add(
+            Message::error()
+                .with_code(IMMUTABLE_THIS)
+                .with_message("cannot mutate 'this' without a reference")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn analysis_dangling_reference(&self, name: Option<NamePart>) {
+        let name = match name {
+            Some(name) => format!("'{}'", part_name(self.db, name)),
+            None => "'this'".to_string(),
+        };
+
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "this may refer to {name} after it goes out of scope"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(DANGLING_REFERENCE)
+                .with_message(format!("reference to {name} outlives it"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn analysis_missing_return(&self, returns: Type) {
+        let returns = pretty_type(self.db, &returns);
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "this returns {returns}, but may reach its end without returning"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(MISSING_RETURN)
+                .with_message("missing return")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn analysis_uninitialized_local(&self, name: NamePart) {
+        let name = part_name(self.db, name);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("'{name}' may not have been given a value yet"))];
+
+        self.add(
+            Message::error()
+                .with_code(UNINITIALIZED_LOCAL)
+                .with_message(format!("use of possibly uninitialized local '{name}'"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn analysis_unreachable_statement(&self) {
+        let labels =
+            vec![Label::primary(self.span).with_message("this statement can never be reached")];
+
+        self.add(
+            Message::warning()
+                .with_code(UNREACHABLE_STATEMENT)
+                .with_message("unreachable statement")
+                .with_labels(labels),
+        );
+    }
+}
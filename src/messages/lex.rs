@@ -0,0 +1,16 @@
+use super::{Label, Message, MessageMaker};
+
+const UNTERMINATED_COMMENT: &str = "EL00";
+
+impl MessageMaker<'_> {
+    pub fn lex_unterminated_comment(&self) {
+        let labels = vec![Label::primary(self.span).with_message("this comment is never closed")];
+
+        self.add(
+            Message::error()
+                .with_code(UNTERMINATED_COMMENT)
+                .with_message("unterminated block comment")
+                .with_labels(labels),
+        );
+    }
+}
@@ -1,8 +1,14 @@
+mod analysis;
+mod lex;
 mod parse;
 mod resolve;
 mod types;
 
-use crate::source::Span;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use crate::names::{NameNode, NamePart};
+use crate::source::{Source, Span};
 use crate::{Db, Messages};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -112,14 +118,64 @@ pub enum LabelKind {
 pub struct MessageMaker<'a> {
     db: &'a dyn Db,
     span: Span,
+
+    /// The spans messages have already been added at, if only one message
+    /// should be added per span.
+    reported: Option<&'a RefCell<HashSet<Span>>>,
+
+    /// The source messages should be about, if any.
+    within: Option<Source>,
 }
 
 impl<'a> MessageMaker<'a> {
     pub fn at(db: &'a dyn Db, span: Span) -> Self {
-        Self { db, span }
+        Self {
+            db,
+            span,
+            reported: None,
+            within: None,
+        }
+    }
+
+    /// Drop the message if another message sharing the same `reported` set has
+    /// already been added at this span.
+    pub fn once(self, reported: &'a RefCell<HashSet<Span>>) -> Self {
+        Self {
+            reported: Some(reported),
+            ..self
+        }
+    }
+
+    /// Drop the message unless it is at a span in `source`. Queries which look
+    /// at the items of imported modules leave reporting problems with those
+    /// items to the modules themselves.
+    pub fn within(self, source: Source) -> Self {
+        Self {
+            within: Some(source),
+            ..self
+        }
     }
 
     fn add(&self, message: Message) {
+        if self.within.is_some_and(|source| source != self.span.source) {
+            return;
+        }
+
+        if let Some(reported) = self.reported {
+            if !reported.borrow_mut().insert(self.span) {
+                return;
+            }
+        }
+
         Messages::push(self.db, message);
     }
 }
+
+/// How a name is written in the source.
+fn part_name(db: &dyn Db, name: NamePart) -> String {
+    match name.node(db) {
+        NameNode::Invalid => "<error>".to_string(),
+        NameNode::Type(name) | NameNode::Value(name) => name.clone(),
+        NameNode::Quoted(name) => format!("\"{name}\""),
+    }
+}
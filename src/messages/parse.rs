@@ -1,13 +1,33 @@
 use super::{Label, Message, MessageMaker};
+use crate::source::Span;
 
 const EXPECTED_DECLARATION: &str = "EP00";
 const EXPECTED_EXPRESSION: &str = "EP01";
 const EXPECTED_TYPE: &str = "EP02";
+const EXPECTED_PATTERN: &str = "EP03";
+const EXPECTED_STATEMENT: &str = "EP04";
 const EXPECTED_TYPE_NAME: &str = "EP10";
 const EXPECTED_VALUE_NAME: &str = "EP11";
 const EXPECTED_ASSIGNMENT: &str = "EP12";
+const EXPECTED_PARAMETERS: &str = "EP13";
+const FUNCTION_VARIANCE: &str = "EP14";
+const UNKNOWN_ATTRIBUTE: &str = "EP15";
+const MISPLACED_RUNTIME: &str = "EP16";
 const MISSING_END: &str = "EP20";
 const MISSING_PAREN: &str = "EP21";
+const UNEXPECTED_IN_PARENS: &str = "EP22";
+const MISSING_BRACKET: &str = "EP23";
+const CHAINED_COMPARISON: &str = "EP30";
+const INVALID_ESCAPE: &str = "EP40";
+const UNCLOSED_INTERPOLATION: &str = "EP41";
+const UNMATCHED_BRACE: &str = "EP42";
+const UNEXPECTED_INTERPOLATION: &str = "EP43";
+const EXPECTED_INTERPOLATION_END: &str = "EP44";
+const NUMBER_MISSING_DIGITS: &str = "EP50";
+const NUMBER_INVALID_DIGIT: &str = "EP51";
+const NUMBER_UNKNOWN_SUFFIX: &str = "EP52";
+const NUMBER_TOO_LARGE: &str = "EP53";
+const NUMBER_FRACTIONAL_RADIX: &str = "EP54";
 
 impl MessageMaker<'_> {
     pub fn parse_expected_declaration(&self) {
@@ -43,6 +63,28 @@ impl MessageMaker<'_> {
         );
     }
 
+    pub fn parse_expected_pattern(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(EXPECTED_PATTERN)
+                .with_message("expected a pattern")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_expected_statement(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(EXPECTED_STATEMENT)
+                .with_message("expected a statement")
+                .with_labels(labels),
+        );
+    }
+
     pub fn parse_expected_value_name(&self, type_name: Option<&str>) {
         let mut labels = vec![Label::primary(self.span)];
 
@@ -90,6 +132,17 @@ impl MessageMaker<'_> {
         );
     }
 
+    pub fn parse_expected_parameters(&self) {
+        let labels = vec![Label::primary(self.span).with_message("expected '('")];
+
+        self.add(
+            Message::error()
+                .with_code(EXPECTED_PARAMETERS)
+                .with_message("expected a parameter list")
+                .with_labels(labels),
+        );
+    }
+
     pub fn parse_missing_end(&self) {
         let labels = vec![Label::primary(self.span)];
 
@@ -111,6 +164,219 @@ impl MessageMaker<'_> {
                 .with_labels(labels),
         );
     }
+
+    pub fn parse_function_variance(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("only type parameters of classes and variants have a variance")];
+
+        self.add(
+            Message::error()
+                .with_code(FUNCTION_VARIANCE)
+                .with_message("variance annotation on a function type parameter")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_unknown_attribute(&self, name: &str) {
+        let labels =
+            vec![Label::primary(self.span).with_message("the only attribute is '@runtime'")];
+
+        self.add(
+            Message::error()
+                .with_code(UNKNOWN_ATTRIBUTE)
+                .with_message(format!("unknown attribute '@{name}'"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_misplaced_runtime(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("only functions without a body can be provided by the runtime")];
+
+        self.add(
+            Message::error()
+                .with_code(MISPLACED_RUNTIME)
+                .with_message("'@runtime' on a declaration with a definition")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_missing_bracket(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(MISSING_BRACKET)
+                .with_message("unclosed opening bracket")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_unexpected_in_parens(&self, opener: Span) {
+        let labels = vec![
+            Label::primary(self.span).with_message("expected ')'"),
+            Label::note(opener).with_message("parenthesis opened here"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(UNEXPECTED_IN_PARENS)
+                .with_message("unexpected tokens before closing parenthesis")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_chained_comparison(&self, previous: Span) {
+        let labels = vec![
+            Label::primary(self.span),
+            Label::note(previous).with_message("previous comparison here"),
+            Label::help(self.span).with_message("add parentheses to group the comparisons"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(CHAINED_COMPARISON)
+                .with_message("comparison operators cannot be chained")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_invalid_escape(&self) {
+        let labels = vec![
+            Label::primary(self.span),
+            Label::help(self.span).with_message(
+                "valid escapes are '\\n', '\\t', '\\\\', '\\\"', '\\{', '\\}' and '\\u{...}'",
+            ),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(INVALID_ESCAPE)
+                .with_message("invalid escape sequence")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_unclosed_interpolation(&self) {
+        let labels = vec![Label::primary(self.span).with_message("this '{' is never closed")];
+
+        self.add(
+            Message::error()
+                .with_code(UNCLOSED_INTERPOLATION)
+                .with_message("unclosed interpolation")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_unmatched_brace(&self) {
+        let labels = vec![
+            Label::primary(self.span),
+            Label::help(self.span).with_message("use '\\}' for a literal brace"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(UNMATCHED_BRACE)
+                .with_message("unmatched '}' in string")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_unexpected_interpolation(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(UNEXPECTED_INTERPOLATION)
+                .with_message("interpolation is not allowed here")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_expected_interpolation_end(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(EXPECTED_INTERPOLATION_END)
+                .with_message("expected the end of the interpolation")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_number_missing_digits(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(NUMBER_MISSING_DIGITS)
+                .with_message("number literal has no digits")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_number_invalid_digit(&self, digit: char, radix: u32) {
+        let kind = radix_name(radix);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("'{digit}' is not a valid {kind} digit"))];
+
+        self.add(
+            Message::error()
+                .with_code(NUMBER_INVALID_DIGIT)
+                .with_message("invalid digit in number literal")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_number_unknown_suffix(&self, suffix: &str) {
+        let labels = vec![
+            Label::primary(self.span).with_message(format!("unknown suffix '{suffix}'")),
+            Label::help(self.span).with_message("valid suffixes are 'int' and 'nat'"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(NUMBER_UNKNOWN_SUFFIX)
+                .with_message("unknown number literal suffix")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_number_too_large(&self) {
+        let labels = vec![Label::primary(self.span)];
+
+        self.add(
+            Message::error()
+                .with_code(NUMBER_TOO_LARGE)
+                .with_message("number literal is too large to represent")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn parse_number_fractional_radix(&self, radix: u32) {
+        let kind = radix_name(radix);
+        let labels = vec![
+            Label::primary(self.span),
+            Label::help(self.span).with_message("only decimal literals can have a fractional part"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(NUMBER_FRACTIONAL_RADIX)
+                .with_message(format!("fractional {kind} literals are not supported"))
+                .with_labels(labels),
+        );
+    }
+}
+
+/// The name of the base of a number literal, like "hexadecimal".
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary",
+        8 => "octal",
+        16 => "hexadecimal",
+        _ => "decimal",
+    }
 }
 
 fn make_type_case(name: &str) -> String {
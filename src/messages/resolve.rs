@@ -1,9 +1,22 @@
+use itertools::Itertools;
+
 use crate::source::Span;
 
-use super::{Label, Message, MessageMaker};
+use super::{part_name, Label, Message, MessageMaker};
+use crate::names::NamePart;
 
 const DUPLICATE_DEFINITIONS: &str = "ER00";
 const UNRESOLVED_NAME: &str = "ER01";
+const UNKNOWN_OPERATOR: &str = "ER02";
+const OUTSIDE_LOOP: &str = "ER03";
+const DUPLICATE_LOCAL: &str = "ER04";
+const PRIVATE_NAME: &str = "ER05";
+const UNKNOWN_MODULE: &str = "ER06";
+const AMBIGUOUS_MODULE: &str = "ER07";
+const IMPORT_CYCLE: &str = "ER08";
+const AMBIGUOUS_NAME: &str = "ER09";
+const SHADOWED: &str = "WR00";
+const UNUSED_LOCAL: &str = "WR01";
 
 impl MessageMaker<'_> {
     pub fn resolve_duplicate_definitions(&self, other: Span) {
@@ -20,6 +33,62 @@ impl MessageMaker<'_> {
         );
     }
 
+    pub fn resolve_duplicate_local(&self, other: Span) {
+        let labels = vec![
+            Label::primary(self.span).with_message("declared again here"),
+            Label::note(other).with_message("first declared here"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(DUPLICATE_LOCAL)
+                .with_message("local declared twice in the same scope")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn resolve_private_name(&self, name: NamePart, declared: Span) {
+        let name = part_name(self.db, name);
+        let labels = vec![
+            Label::primary(self.span).with_message(format!("'{name}' is not visible here")),
+            Label::note(declared).with_message(
+                "it is declared private here, so it can only be used within its class",
+            ),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(PRIVATE_NAME)
+                .with_message(format!("'{name}' is private"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn resolve_shadowed(&self, other: Span) {
+        let labels = vec![
+            Label::primary(self.span).with_message("this shadows an earlier declaration"),
+            Label::note(other).with_message("which is declared here"),
+        ];
+
+        self.add(
+            Message::warning()
+                .with_code(SHADOWED)
+                .with_message("shadowed name")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn resolve_unused_local(&self) {
+        let labels = vec![Label::primary(self.span).with_message("this is never used")];
+
+        self.add(
+            Message::warning()
+                .with_code(UNUSED_LOCAL)
+                .with_message("unused local")
+                .with_labels(labels),
+        );
+    }
+
     pub fn resolve_unresolved_name(&self) {
         let labels = vec![Label::primary(self.span)];
 
@@ -30,4 +99,91 @@ impl MessageMaker<'_> {
                 .with_labels(labels),
         )
     }
+
+    pub fn resolve_outside_loop(&self, keyword: &str) {
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("'{keyword}' can only be used inside a loop"))];
+
+        self.add(
+            Message::error()
+                .with_code(OUTSIDE_LOOP)
+                .with_message(format!("'{keyword}' outside of a loop"))
+                .with_labels(labels),
+        )
+    }
+
+    pub fn resolve_unknown_operator(&self, name: &str) {
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("'{name}' is not the name of an operator"))];
+
+        self.add(
+            Message::error()
+                .with_code(UNKNOWN_OPERATOR)
+                .with_message("unknown operator name")
+                .with_labels(labels),
+        )
+    }
+
+    pub fn resolve_unknown_module(&self, module: &str) {
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("no source in the project is called '{module}'"))];
+
+        self.add(
+            Message::error()
+                .with_code(UNKNOWN_MODULE)
+                .with_message("unknown module")
+                .with_labels(labels),
+        )
+    }
+
+    pub fn resolve_ambiguous_module(&self, sources: Vec<String>) {
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "this could refer to any of {}",
+            sources.iter().map(|name| format!("'{name}'")).join(", ")
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(AMBIGUOUS_MODULE)
+                .with_message("ambiguous import")
+                .with_labels(labels),
+        )
+    }
+
+    /// Report an import which leads back to the importing module, through the
+    /// given modules.
+    pub fn resolve_import_cycle(&self, through: Vec<String>) {
+        let label = if through.is_empty() {
+            "this module imports itself".to_string()
+        } else {
+            format!(
+                "this module ends up importing itself through {}",
+                through.iter().map(|name| format!("'{name}'")).join(", ")
+            )
+        };
+
+        self.add(
+            Message::error()
+                .with_code(IMPORT_CYCLE)
+                .with_message("import cycle")
+                .with_labels(vec![Label::primary(self.span).with_message(label)]),
+        )
+    }
+
+    pub fn resolve_ambiguous_name(&self, name: NamePart, declared: Vec<(String, Span)>) {
+        let name = part_name(self.db, name);
+        let mut labels = vec![Label::primary(self.span)
+            .with_message(format!("'{name}' is imported from more than one module"))];
+
+        labels.extend(declared.into_iter().map(|(module, span)| {
+            Label::note(span).with_message(format!("it is declared in '{module}' here"))
+        }));
+
+        self.add(
+            Message::error()
+                .with_code(AMBIGUOUS_NAME)
+                .with_message(format!("ambiguous name '{name}'"))
+                .with_labels(labels),
+        )
+    }
 }
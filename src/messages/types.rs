@@ -1,9 +1,38 @@
 use itertools::Itertools;
 
-use super::{Label, Message, MessageMaker};
+use super::{part_name, Label, Message, MessageMaker};
+use crate::ast::Variance;
+use crate::names::NamePart;
+use crate::source::Span;
 use crate::types::{pretty_type, Type};
 
 const SUBTYPE_CYCLE: &str = "ET00";
+const LITERAL_OUT_OF_RANGE: &str = "ET01";
+const FRACTIONAL_LITERAL: &str = "ET02";
+const WRONG_TYPE_ARITY: &str = "ET03";
+const VARIANCE_MISMATCH: &str = "ET04";
+const INCONSISTENT_HIERARCHY: &str = "ET05";
+const AMBIGUOUS_MEMBER: &str = "ET06";
+const CLOSED_VARIANT: &str = "ET07";
+const MISMATCHED_TYPES: &str = "ET10";
+const NOT_CALLABLE: &str = "ET11";
+const WRONG_ARGUMENT_COUNT: &str = "ET12";
+const NO_SUCH_MEMBER: &str = "ET13";
+const NO_OPERATOR: &str = "ET14";
+const CANNOT_INFER: &str = "ET15";
+const INVALID_ASSIGNMENT: &str = "ET16";
+const THIS_OUTSIDE_METHOD: &str = "ET17";
+const CLASS_AS_VALUE: &str = "ET18";
+const MISSING_BUILTIN: &str = "ET19";
+const OVERRIDE_OUTSIDE_CLASS: &str = "ET20";
+const OVERRIDE_NOT_INHERITED: &str = "ET21";
+const OVERRIDE_NO_MEMBER: &str = "ET22";
+const OVERRIDE_MISMATCH: &str = "ET23";
+const OVERRIDE_THIS_MISMATCH: &str = "ET24";
+const MISSING_IMPLEMENTATIONS: &str = "ET25";
+const ABSTRACT_INSTANCE: &str = "ET26";
+const MISSING_ARMS: &str = "ET30";
+const UNREACHABLE_ARM: &str = "WT30";
 
 impl MessageMaker<'_> {
     pub fn types_subtype_cycle(&self, involves: Option<Vec<Type>>) {
@@ -30,4 +59,401 @@ impl MessageMaker<'_> {
                 .with_labels(labels),
         );
     }
+
+    pub fn types_literal_out_of_range(&self, ty: Type, min: i128, max: i128) {
+        let ty = pretty_type(self.db, &ty);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("values of type {ty} range from {min} to {max}"))];
+
+        self.add(
+            Message::error()
+                .with_code(LITERAL_OUT_OF_RANGE)
+                .with_message(format!("literal out of range for {ty}"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_fractional_literal(&self, ty: Type) {
+        let ty = pretty_type(self.db, &ty);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("values of type {ty} cannot have a fractional part"))];
+
+        self.add(
+            Message::error()
+                .with_code(FRACTIONAL_LITERAL)
+                .with_message("fractional number literal")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_wrong_type_arity(&self, expected: usize, found: usize) {
+        let plural = if expected == 1 { "" } else { "s" };
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "expected {expected} type argument{plural}, found {found}"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(WRONG_TYPE_ARITY)
+                .with_message("wrong number of type arguments")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_variance_mismatch(&self, declared: Span, variance: Variance, position: Variance) {
+        let variance = variance_name(variance);
+        let position = match position {
+            Variance::Invariant => "an invariant",
+            Variance::Covariant => "a covariant",
+            Variance::Contravariant => "a contravariant",
+        };
+
+        let labels = vec![
+            Label::primary(self.span).with_message(format!(
+                "this type parameter is used in {position} position"
+            )),
+            Label::note(declared).with_message(format!("declared {variance} here")),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(VARIANCE_MISMATCH)
+                .with_message(format!(
+                    "{variance} type parameter used in {position} position"
+                ))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_inconsistent_hierarchy(&self, class: Type) {
+        let class = pretty_type(self.db, &class);
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "the parents of {class} are inherited in conflicting orders"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(INCONSISTENT_HIERARCHY)
+                .with_message(format!("cannot linearize the supertypes of {class}"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_ambiguous_member(&self, class: Type, name: NamePart, declared: Vec<Span>) {
+        let class = pretty_type(self.db, &class);
+        let name = part_name(self.db, name);
+
+        let mut labels = vec![Label::primary(self.span).with_message(format!(
+            "{class} inherits more than one '{name}' without overriding it"
+        ))];
+        labels.extend(
+            declared
+                .into_iter()
+                .map(|span| Label::note(span).with_message("one is declared here")),
+        );
+
+        self.add(
+            Message::error()
+                .with_code(AMBIGUOUS_MEMBER)
+                .with_message(format!("ambiguous member '{name}'"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_closed_variant(&self, variant: Type) {
+        let variant = pretty_type(self.db, &variant);
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "{variant} is a variant, so its cases are nested within it"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(CLOSED_VARIANT)
+                .with_message(format!("cannot inherit from {variant} outside of its body"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_mismatched(&self, expected: Type, found: Type) {
+        let expected = pretty_type(self.db, &expected);
+        let found = pretty_type(self.db, &found);
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("expected {expected}, found {found}"))];
+
+        self.add(
+            Message::error()
+                .with_code(MISMATCHED_TYPES)
+                .with_message("mismatched types")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_not_callable(&self, ty: Type) {
+        let ty = pretty_type(self.db, &ty);
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("values of type {ty} cannot be called"))];
+
+        self.add(
+            Message::error()
+                .with_code(NOT_CALLABLE)
+                .with_message("call of a non-function")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_wrong_argument_count(&self, expected: usize, found: usize) {
+        let plural = if expected == 1 { "" } else { "s" };
+        let labels = vec![Label::primary(self.span).with_message(format!(
+            "expected {expected} argument{plural}, found {found}"
+        ))];
+
+        self.add(
+            Message::error()
+                .with_code(WRONG_ARGUMENT_COUNT)
+                .with_message("wrong number of arguments")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_no_such_member(&self, ty: Type, name: NamePart) {
+        let ty = pretty_type(self.db, &ty);
+        let name = part_name(self.db, name);
+
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("{ty} has no member named '{name}'"))];
+
+        self.add(
+            Message::error()
+                .with_code(NO_SUCH_MEMBER)
+                .with_message("no such member")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_no_operator(&self, ty: Type, operator: &str) {
+        let ty = pretty_type(self.db, &ty);
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("{ty} does not implement '{operator}'"))];
+
+        self.add(
+            Message::error()
+                .with_code(NO_OPERATOR)
+                .with_message(format!("operator '{operator}' cannot be used here"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_cannot_infer(&self) {
+        let labels =
+            vec![Label::primary(self.span).with_message("consider adding a type annotation")];
+
+        self.add(
+            Message::error()
+                .with_code(CANNOT_INFER)
+                .with_message("cannot infer a type")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_invalid_assignment(&self) {
+        let labels =
+            vec![Label::primary(self.span)
+                .with_message("only variables and fields can be assigned to")];
+
+        self.add(
+            Message::error()
+                .with_code(INVALID_ASSIGNMENT)
+                .with_message("invalid assignment target")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_this_outside_method(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("'this' can only be used in functions which take it as an argument")];
+
+        self.add(
+            Message::error()
+                .with_code(THIS_OUTSIDE_METHOD)
+                .with_message("'this' outside of a method")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_class_as_value(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("classes can only be called or have their members accessed")];
+
+        self.add(
+            Message::error()
+                .with_code(CLASS_AS_VALUE)
+                .with_message("class used as a value")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_missing_builtin(&self, name: &str) {
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("this needs a class named '{name}' to be declared"))];
+
+        self.add(
+            Message::error()
+                .with_code(MISSING_BUILTIN)
+                .with_message(format!("no '{name}' class"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_override_outside_class(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("only members of a class can override members of its parents")];
+
+        self.add(
+            Message::error()
+                .with_code(OVERRIDE_OUTSIDE_CLASS)
+                .with_message("override outside of a class")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_override_not_inherited(&self, parent: Type) {
+        let parent = pretty_type(self.db, &parent);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("this class does not inherit from {parent}"))];
+
+        self.add(
+            Message::error()
+                .with_code(OVERRIDE_NOT_INHERITED)
+                .with_message(format!("cannot override a member of {parent}"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_override_no_member(&self, parent: Type, name: NamePart) {
+        let parent = pretty_type(self.db, &parent);
+        let name = part_name(self.db, name);
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("{parent} has no member named '{name}'"))];
+
+        self.add(
+            Message::error()
+                .with_code(OVERRIDE_NO_MEMBER)
+                .with_message("override of a nonexistent member")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_override_mismatch(&self, declared: Span, expected: Type, found: Type) {
+        let expected = pretty_type(self.db, &expected);
+        let found = pretty_type(self.db, &found);
+        let labels = vec![
+            Label::primary(self.span)
+                .with_message(format!("expected a subtype of {expected}, found {found}")),
+            Label::note(declared).with_message("overridden member declared here"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(OVERRIDE_MISMATCH)
+                .with_message("incompatible override")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_override_this_mismatch(&self, declared: Span, expected: Option<usize>) {
+        let expected = match expected {
+            Some(refs) => format!("take 'this{}'", "&".repeat(refs)),
+            None => "not take 'this'".to_string(),
+        };
+
+        let labels = vec![
+            Label::primary(self.span).with_message(format!("this override should {expected}")),
+            Label::note(declared).with_message("overridden member declared here"),
+        ];
+
+        self.add(
+            Message::error()
+                .with_code(OVERRIDE_THIS_MISMATCH)
+                .with_message("override takes 'this' differently")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_missing_implementations(&self, class: Type, missing: Vec<(NamePart, Span)>) {
+        let class = pretty_type(self.db, &class);
+        let names = missing
+            .iter()
+            .map(|(name, _)| format!("'{}'", part_name(self.db, *name)))
+            .join(", ");
+
+        let mut labels =
+            vec![Label::primary(self.span)
+                .with_message(format!("{class} does not implement {names}"))];
+        labels.extend(missing.into_iter().map(|(name, span)| {
+            let name = part_name(self.db, name);
+            Label::note(span).with_message(format!("'{name}' declared here"))
+        }));
+
+        self.add(
+            Message::error()
+                .with_code(MISSING_IMPLEMENTATIONS)
+                .with_message("missing implementations of abstract members")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_abstract_instance(&self, class: Type, missing: Vec<(NamePart, Span)>) {
+        let class = pretty_type(self.db, &class);
+        let mut labels = vec![Label::primary(self.span)
+            .with_message(format!("{class} has members without an implementation"))];
+        labels.extend(missing.into_iter().map(|(name, span)| {
+            let name = part_name(self.db, name);
+            Label::note(span).with_message(format!("'{name}' declared here"))
+        }));
+
+        self.add(
+            Message::error()
+                .with_code(ABSTRACT_INSTANCE)
+                .with_message(format!("cannot create an instance of {class}"))
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_missing_arms(&self, missing: Vec<Type>) {
+        let missing = missing.iter().map(|ty| pretty_type(self.db, ty)).join(", ");
+
+        let labels = vec![Label::primary(self.span)
+            .with_message(format!("instances of {missing} are not matched"))];
+
+        self.add(
+            Message::error()
+                .with_code(MISSING_ARMS)
+                .with_message("case does not cover every possibility")
+                .with_labels(labels),
+        );
+    }
+
+    pub fn types_unreachable_arm(&self) {
+        let labels = vec![Label::primary(self.span)
+            .with_message("everything this matches is matched by earlier arms")];
+
+        self.add(
+            Message::warning()
+                .with_code(UNREACHABLE_ARM)
+                .with_message("unreachable case arm")
+                .with_labels(labels),
+        );
+    }
+}
+
+fn variance_name(variance: Variance) -> &'static str {
+    match variance {
+        Variance::Invariant => "invariant",
+        Variance::Covariant => "covariant",
+        Variance::Contravariant => "contravariant",
+    }
 }
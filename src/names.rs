@@ -11,6 +11,10 @@ pub struct NamePart {
 pub enum NameNode {
     Type(String),
     Value(String),
+
+    /// A quoted name like `"+"`, used to declare operator functions.
+    Quoted(String),
+
     Invalid,
 }
 
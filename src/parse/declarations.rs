@@ -1,49 +1,186 @@
 use super::Parser;
 use crate::ast::{
-    Declaration, DeclarationName, DeclarationNameNode, DeclarationNode, Declarations, Type,
-    TypeNode,
+    Declaration, DeclarationName, DeclarationNameNode, DeclarationNode, Import, Type, TypeNode,
+    TypeParameter, Variance,
 };
+use crate::cst::NodeKind;
 use crate::names::{NameNode, NamePart};
 use crate::source::Span;
 use crate::token::Token;
 
 impl Parser<'_> {
-    pub const DECLARATION_START: &[Token] =
-        &[Token::Class, Token::Function, Token::Var, Token::Variant];
-
-    pub fn parse_top_level(&mut self) -> Declarations {
+    pub const DECLARATION_START: &[Token] = &[
+        Token::Attribute(String::new()),
+        Token::Class,
+        Token::Function,
+        Token::Var,
+        Token::Variant,
+    ];
+
+    /// The tokens at which a list of declarations resumes after an error.
+    pub const DECLARATION_RECOVERY: &[Token] = &[
+        Token::Attribute(String::new()),
+        Token::Class,
+        Token::Function,
+        Token::Var,
+        Token::Variant,
+        Token::End,
+        Token::Private,
+    ];
+
+    /// The tokens at which the top level resumes after an error.
+    pub const TOP_LEVEL_START: &[Token] = &[
+        Token::Attribute(String::new()),
+        Token::Class,
+        Token::Function,
+        Token::Import,
+        Token::Var,
+        Token::Variant,
+    ];
+
+    pub fn parse_top_level(&mut self) -> (Vec<Import>, Vec<Declaration>) {
+        let mut imports = Vec::new();
         let mut declarations = Vec::new();
 
         // Parse the entire thing
         while !self.is_done() {
-            declarations.extend(self.declaration());
+            if self.matches(Token::Import).is_some() {
+                imports.push(self.import());
+            } else if self.matches(Self::DECLARATION_START).is_some() {
+                declarations.extend(self.declaration());
+            } else {
+                let span = self.recover(Self::TOP_LEVEL_START);
+                self.at(span).parse_expected_declaration();
+            }
         }
 
-        Declarations::new(self.db, declarations)
+        (imports, declarations)
+    }
+
+    /// ```abnf
+    /// import = "import" VALUE_NAME *("." VALUE_NAME)
+    /// ```
+    fn import(&mut self) -> Import {
+        self.node(NodeKind::Import, |this| {
+            let mut span = this
+                .consume(Token::Import)
+                .expect("imports begin with 'import'");
+
+            let mut module = Vec::new();
+            loop {
+                let (name, name_span) = this.parse_name(|this, name, span| {
+                    match name.node(this.db) {
+                        NameNode::Value(_) => {}
+                        NameNode::Type(name) => {
+                            this.at(span).parse_expected_value_name(Some(name.as_str()))
+                        }
+
+                        NameNode::Quoted(_) | NameNode::Invalid => {
+                            this.at(span).parse_expected_value_name(None)
+                        }
+                    }
+
+                    name
+                });
+
+                if name.node(this.db) == &NameNode::Invalid {
+                    module.push(name);
+                    break;
+                }
+
+                span += name_span;
+                module.push(name);
+
+                if this.consume(Token::Dot).is_none() {
+                    break;
+                }
+            }
+
+            Import { module, span }
+        })
     }
 
     pub fn parse_declarations(&mut self) -> (Vec<Declaration>, Vec<Declaration>) {
-        let mut public = Vec::new();
-        let mut private = Vec::new();
+        let public = self.declaration_list();
+        let private = self
+            .consume(Token::Private)
+            .map(|_| self.declaration_list())
+            .unwrap_or_default();
 
-        while self.matches(Self::DECLARATION_START).is_some() {
-            public.extend(self.declaration());
-        }
+        (public, private)
+    }
 
-        if self.consume(Token::Private).is_some() {
-            while self.matches(Self::DECLARATION_START).is_some() {
-                private.extend(self.declaration());
+    /// Parse declarations up to the `end` or `private` of the enclosing class
+    /// or variant, skipping past anything else.
+    fn declaration_list(&mut self) -> Vec<Declaration> {
+        let mut declarations = Vec::new();
+
+        loop {
+            if self.matches(Self::DECLARATION_START).is_some() {
+                declarations.extend(self.declaration());
+            } else if self.is_done() || self.matches(&[Token::End, Token::Private][..]).is_some() {
+                break;
+            } else {
+                let span = self.recover(Self::DECLARATION_RECOVERY);
+                self.at(span).parse_expected_declaration();
             }
         }
 
-        (public, private)
+        declarations
     }
 
     fn declaration(&mut self) -> Option<Declaration> {
+        let docs = std::mem::take(&mut self.docs);
+
+        let syntax = self.start_node(NodeKind::Declaration);
+        let runtime = self.attributes();
+        let parts = self.declaration_parts();
+        self.finish_node();
+
+        let (name, mut node, span) = parts?;
+
+        if let Some(attribute) = runtime {
+            match &mut node {
+                DeclarationNode::Function {
+                    body: None,
+                    runtime,
+                    ..
+                } => *runtime = true,
+                _ => self.at(attribute).parse_misplaced_runtime(),
+            }
+        }
+
+        Some(Declaration::new(self.db, name, node, span, docs, syntax))
+    }
+
+    /// Parse the attributes of a declaration, returning where it is marked as
+    /// `@runtime`, the only attribute there is.
+    ///
+    /// ```abnf
+    /// attribute = ATTRIBUTE
+    /// ```
+    fn attributes(&mut self) -> Option<Span> {
+        let mut runtime = None;
+
+        while let Some((Token::Attribute(name), span)) = self.this_one() {
+            let _ = self.next();
+
+            if name == "runtime" {
+                runtime = Some(*span);
+            } else {
+                self.at(*span).parse_unknown_attribute(name);
+            }
+        }
+
+        runtime
+    }
+
+    fn declaration_parts(&mut self) -> Option<(DeclarationName, DeclarationNode, Span)> {
         let (name, node, span) = match self.this_one() {
             Some((Token::Class, opener)) => {
                 let _ = self.next();
                 let name = self.declaration_name();
+                let type_params = self.type_parameters();
 
                 let inherits = self
                     .consume(Token::Is)
@@ -59,6 +196,7 @@ impl Parser<'_> {
 
                 let span = *opener + end;
                 let node = DeclarationNode::Class {
+                    type_params,
                     public,
                     private,
                     inherits,
@@ -70,6 +208,7 @@ impl Parser<'_> {
             Some((Token::Variant, opener)) => {
                 let _ = self.next();
                 let name = self.declaration_name();
+                let type_params = self.type_parameters();
 
                 let inherits = self
                     .consume(Token::Is)
@@ -85,6 +224,7 @@ impl Parser<'_> {
 
                 let span = *opener + end;
                 let node = DeclarationNode::Variant {
+                    type_params,
                     public,
                     private,
                     inherits,
@@ -96,15 +236,19 @@ impl Parser<'_> {
             Some((Token::Function, opener)) => {
                 let _ = self.next();
                 let name = self.declaration_name();
+                let type_params = self.type_parameters();
+                for param in type_params.iter() {
+                    if param.variance != Variance::Invariant {
+                        self.at(param.span).parse_function_variance();
+                    }
+                }
+
                 let (this, args) = self
                     .consume(Token::OpenParen)
                     .map(|opener| {
                         let parameters = self.parameters();
 
-                        let _ = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                            self.at(opener).parse_missing_paren();
-                            self.closest_span()
-                        });
+                        let _ = self.closing_paren(opener);
 
                         parameters
                     })
@@ -117,12 +261,10 @@ impl Parser<'_> {
                     .unwrap_or(Type {
                         node: TypeNode::Unit,
                         span: self.closest_span(),
+                        syntax: None,
                     });
 
-                let body = self
-                    .matches(Self::STATEMENT_START)
-                    .is_some()
-                    .then(|| self.parse_block());
+                let body = self.at_statement().then(|| self.parse_block());
 
                 let end = if body.is_some() {
                     self.consume(Token::End).unwrap_or_else(|| {
@@ -130,15 +272,17 @@ impl Parser<'_> {
                         self.closest_span()
                     })
                 } else {
-                    self.closest_span()
+                    return_type.span
                 };
 
                 let span = *opener + end;
                 let node = DeclarationNode::Function {
+                    type_params,
                     this,
                     args,
                     return_type,
                     body,
+                    runtime: false,
                 };
 
                 (name, node, span)
@@ -153,7 +297,8 @@ impl Parser<'_> {
                     .consume(Token::ColonEqual)
                     .map(|_| self.parse_expression());
 
-                let span = *opener + self.closest_span();
+                let end = body.as_ref().map(|body| body.span).unwrap_or(anno.span);
+                let span = *opener + end;
                 let node = DeclarationNode::Variable { anno, body };
 
                 (name, node, span)
@@ -171,7 +316,7 @@ impl Parser<'_> {
             }
         };
 
-        Some(Declaration::new(self.db, name, node, span))
+        Some((name, node, span))
     }
 
     fn declaration_name(&mut self) -> DeclarationName {
@@ -212,7 +357,8 @@ impl Parser<'_> {
 
             Some((Token::String(name), span)) => {
                 let _ = self.next();
-                (DeclarationNameNode::Quoted(name.clone()), *span)
+                let name = self.plain_string(name, *span);
+                (DeclarationNameNode::Quoted(name), *span)
             }
 
             Some((tok, span)) => {
@@ -229,6 +375,79 @@ impl Parser<'_> {
         }
     }
 
+    /// ```abnf
+    /// type-params = ["[" [TYPE_NAME *("," TYPE_NAME) [","]] "]"]
+    /// ```
+    fn type_parameters(&mut self) -> Vec<TypeParameter> {
+        let Some(opener) = self.consume(Token::OpenBracket) else {
+            return Vec::new();
+        };
+
+        let mut params = Vec::new();
+        loop {
+            let variance = self.variance();
+            let Some((token @ (Token::TypeName(_) | Token::ValueName(_)), span)) = self.this_one()
+            else {
+                if let Some((_, span)) = variance {
+                    self.at(span).parse_expected_type_name(None);
+                }
+
+                break;
+            };
+
+            let _ = self.next();
+
+            let name = match token {
+                Token::TypeName(name) => NameNode::Type(name.clone()),
+                Token::ValueName(name) => {
+                    self.at(*span).parse_expected_type_name(Some(name));
+                    NameNode::Invalid
+                }
+                _ => unreachable!(),
+            };
+
+            let (variance, span) = match variance {
+                Some((variance, opener)) => (variance, opener + *span),
+                None => (Variance::Invariant, *span),
+            };
+
+            params.push(TypeParameter {
+                variance,
+                name: NamePart::new(self.db, name),
+                span,
+            });
+
+            let _ = self.consume(Token::Comma);
+        }
+
+        if self.consume(Token::CloseBracket).is_none() {
+            self.at(opener).parse_missing_bracket();
+        }
+
+        params
+    }
+
+    /// Parse the variance annotation of a type parameter, if there is one. The
+    /// words `in` and `out` are only special when followed by a name.
+    fn variance(&mut self) -> Option<(Variance, Span)> {
+        let variance = match self.tokens {
+            [(Token::Plus, span), ..] => (Variance::Covariant, *span),
+            [(Token::Minus, span), ..] => (Variance::Contravariant, *span),
+            [(Token::ValueName(word), span), (Token::TypeName(_) | Token::ValueName(_), _), ..] => {
+                match word.as_str() {
+                    "out" => (Variance::Covariant, *span),
+                    "in" => (Variance::Contravariant, *span),
+                    _ => return None,
+                }
+            }
+
+            _ => return None,
+        };
+
+        let _ = self.next();
+        Some(variance)
+    }
+
     fn inherits(&mut self) -> Vec<Type> {
         let mut types = Vec::new();
 
@@ -246,7 +465,7 @@ impl Parser<'_> {
     /// annotated-names = (NAME *("," NAME)) type
     /// this            = "this" / this "&"
     /// ```
-    fn parameters(&mut self) -> (Option<usize>, Vec<(NamePart, Type)>) {
+    fn parameters(&mut self) -> (Option<usize>, Vec<(NamePart, Span, Type)>) {
         let mut names = Vec::new();
         let mut types = Vec::new();
 
@@ -262,10 +481,10 @@ impl Parser<'_> {
             let _ = self.consume(Token::Comma);
         }
 
-        while let Some((Token::ValueName(name), _span)) = self.this_one() {
+        while let Some((Token::ValueName(name), span)) = self.this_one() {
             let _ = self.next();
             let name = NamePart::new(self.db, NameNode::Value(name.clone()));
-            names.push(name);
+            names.push((name, *span));
 
             if self.matches(Self::TYPE_STARTS).is_some() {
                 let ty = self.parse_type();
@@ -277,6 +496,12 @@ impl Parser<'_> {
             let _ = self.consume(Token::Comma);
         }
 
-        (this, names.into_iter().zip(types).collect())
+        let args = names
+            .into_iter()
+            .zip(types)
+            .map(|((name, span), ty)| (name, span, ty))
+            .collect();
+
+        (this, args)
     }
 }
@@ -1,27 +1,124 @@
 use super::Parser;
-use crate::ast::{Expression, ExpressionNode};
+use crate::ast::{BinaryOperator, Expression, ExpressionNode, Lambda, Type, UnaryOperator};
+use crate::cst::NodeKind;
 use crate::names::{NameNode, NamePart};
+use crate::source::Span;
 use crate::token::Token;
 
 impl Parser<'_> {
     pub const EXPR_STARTS: &[Token] = &[
         Token::ValueName(String::new()),
+        Token::TypeName(String::new()),
         Token::Number(String::new()),
         Token::String(String::new()),
+        Token::This,
+        Token::Function,
+        Token::Minus,
         Token::OpenParen,
     ];
 
     /// ```abnf
-    /// expr = postfix-expr
+    /// expr = comparison-expr
     /// ```
     pub fn parse_expression(&mut self) -> Expression {
-        self.prefix_expr()
+        self.binary_expr(0)
+    }
+
+    /// Parse a binary expression using precedence climbing, where every
+    /// operator binds at least as tightly as `min`.
+    ///
+    /// ```abnf
+    /// comparison-expr     = additive-expr [("<" / "=" / ">") additive-expr]
+    /// additive-expr       = multiplicative-expr *(("+" / "-") multiplicative-expr)
+    /// multiplicative-expr = unary-expr *(("*" / "/") unary-expr)
+    /// ```
+    fn binary_expr(&mut self, min: u8) -> Expression {
+        let checkpoint = self.checkpoint();
+        let mut lhs = self.unary_expr();
+
+        while let Some((op, span)) = self.binary_operator() {
+            let (left, right) = Self::binding_power(op);
+            if left < min {
+                break;
+            }
+
+            let _ = self.next();
+            let rhs = self.binary_expr(right);
+
+            if op.is_comparison() {
+                if let Some((next, next_span)) = self.binary_operator() {
+                    if next.is_comparison() {
+                        self.at(next_span).parse_chained_comparison(span);
+                    }
+                }
+            }
+
+            let span = lhs.span + rhs.span;
+            lhs = Expression {
+                node: ExpressionNode::Binary(op, Box::new(lhs), Box::new(rhs)),
+                span,
+                syntax: self.wrap(checkpoint, NodeKind::Expression),
+            };
+        }
+
+        lhs
+    }
+
+    /// The binding power of each binary operator, as a `(left, right)` pair.
+    /// Operators with a higher binding power bind more tightly, and a right
+    /// power greater than the left one makes the operator left associative.
+    fn binding_power(op: BinaryOperator) -> (u8, u8) {
+        match op {
+            BinaryOperator::Less | BinaryOperator::Equal | BinaryOperator::Greater => (1, 2),
+            BinaryOperator::Add | BinaryOperator::Subtract => (3, 4),
+            BinaryOperator::Multiply | BinaryOperator::Divide => (5, 6),
+        }
+    }
+
+    fn binary_operator(&self) -> Option<(BinaryOperator, Span)> {
+        let (token, span) = self.this_one()?;
+        let op = match token {
+            Token::Less => BinaryOperator::Less,
+            Token::Equal => BinaryOperator::Equal,
+            Token::Greater => BinaryOperator::Greater,
+            Token::Plus => BinaryOperator::Add,
+            Token::Minus => BinaryOperator::Subtract,
+            Token::Star => BinaryOperator::Multiply,
+            Token::Slash => BinaryOperator::Divide,
+            _ => return None,
+        };
+
+        Some((op, *span))
+    }
+
+    /// Negation calls `"neg"` rather than `"-"` like subtraction does; see
+    /// [`UnaryOperator`].
+    ///
+    /// ```abnf
+    /// unary-expr = "-" unary-expr / postfix-expr
+    /// ```
+    fn unary_expr(&mut self) -> Expression {
+        let checkpoint = self.checkpoint();
+
+        if let Some(opener) = self.consume(Token::Minus) {
+            let expr = self.unary_expr();
+            let span = opener + expr.span;
+
+            Expression {
+                node: ExpressionNode::Unary(UnaryOperator::Negate, Box::new(expr)),
+                span,
+                syntax: self.wrap(checkpoint, NodeKind::Expression),
+            }
+        } else {
+            self.postfix_expr()
+        }
     }
 
     /// ```abnf
     /// postfix-expr = long-expr ["&"]
     /// ```
-    fn prefix_expr(&mut self) -> Expression {
+    fn postfix_expr(&mut self) -> Expression {
+        let checkpoint = self.checkpoint();
         let expr = self.long_expr();
 
         if let Some(end) = self.consume(Token::Ampersand) {
@@ -30,6 +127,7 @@ impl Parser<'_> {
             Expression {
                 node: ExpressionNode::Reference(Box::new(expr)),
                 span,
+                syntax: self.wrap(checkpoint, NodeKind::Expression),
             }
         } else {
             expr
@@ -39,34 +137,42 @@ impl Parser<'_> {
     /// ```abnf
     /// long-expr  = call-expr / field-expr / simple-expr
     /// call-expr  = long-expr "(" expr-list ")"
-    /// field-expr = long-expr "." (VALUE_NAME / TYPE_NAME)
+    /// field-expr = long-expr "." (VALUE_NAME / TYPE_NAME / STRING)
     /// ```
     fn long_expr(&mut self) -> Expression {
+        let checkpoint = self.checkpoint();
         let mut expr = self.simple_expr();
 
         loop {
             if let Some(opener) = self.consume(Token::OpenParen) {
                 let args = self.expr_list();
-                let closer = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                    self.at(opener).parse_missing_paren();
-                    args.last().map(|ty| ty.span).unwrap_or(opener)
-                });
+                let closer = self
+                    .closing_paren(opener)
+                    .unwrap_or_else(|| args.last().map(|ty| ty.span).unwrap_or(opener));
 
                 let span = expr.span + closer;
 
                 expr = Expression {
                     node: ExpressionNode::Call(Box::new(expr), args),
                     span,
+                    syntax: self.wrap(checkpoint, NodeKind::Expression),
                 };
             } else if self.consume(Token::Dot).is_some() {
                 let span = expr.span;
 
-                let (node, closer) =
-                    self.parse_name(|_, name, _| ExpressionNode::Field(Box::new(expr), name));
+                let (node, closer) = if let Some((Token::String(name), closer)) = self.this_one() {
+                    let _ = self.next();
+                    let name = self.plain_string(name, *closer);
+                    let name = NamePart::new(self.db, NameNode::Quoted(name));
+                    (ExpressionNode::Field(Box::new(expr), name), *closer)
+                } else {
+                    self.parse_name(|_, name, _| ExpressionNode::Field(Box::new(expr), name))
+                };
 
                 let span = span + closer;
+                let syntax = self.wrap(checkpoint, NodeKind::Expression);
 
-                expr = Expression { node, span };
+                expr = Expression { node, span, syntax };
             } else {
                 break;
             }
@@ -76,10 +182,12 @@ impl Parser<'_> {
     }
 
     /// ```abnf
-    /// simple-expr  = VALUE_NAME / TYPE_NAME / NUMBER / STRING
+    /// simple-expr  = VALUE_NAME / TYPE_NAME / NUMBER / STRING / "this"
     /// simple-expr =/ "(" expr ")"
+    /// simple-expr =/ lambda
     /// ```
     fn simple_expr(&mut self) -> Expression {
+        let checkpoint = self.checkpoint();
         let (node, span) = match self.this_one() {
             Some((Token::ValueName(name), span)) => {
                 let _ = self.next();
@@ -95,12 +203,12 @@ impl Parser<'_> {
 
             Some((Token::Number(number), span)) => {
                 let _ = self.next();
-                (ExpressionNode::Number(number.clone()), *span)
+                (self.number_literal(number, *span), *span)
             }
 
             Some((Token::String(string), span)) => {
                 let _ = self.next();
-                (ExpressionNode::String(string.clone()), *span)
+                (self.string_literal(string, *span), *span)
             }
 
             Some((Token::This, span)) => {
@@ -108,14 +216,16 @@ impl Parser<'_> {
                 (ExpressionNode::This, *span)
             }
 
+            Some((Token::Function, opener)) => {
+                let _ = self.next();
+                self.lambda(*opener)
+            }
+
             Some((Token::OpenParen, opener)) => {
                 let _ = self.next();
 
                 let expr = self.parse_expression();
-                let _closer = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                    self.at(*opener).parse_missing_paren();
-                    expr.span
-                });
+                let _ = self.closing_paren(*opener);
 
                 return expr;
             }
@@ -132,7 +242,77 @@ impl Parser<'_> {
             }
         };
 
-        Expression { node, span }
+        let syntax = self.wrap(checkpoint, NodeKind::Expression);
+        Expression { node, span, syntax }
+    }
+
+    /// Parse a lambda after its opening `function` keyword.
+    ///
+    /// ```abnf
+    /// lambda = "function" "(" lambda-parameters ")" [type] *statement "end"
+    /// ```
+    fn lambda(&mut self, opener: Span) -> (ExpressionNode, Span) {
+        let params = if let Some(paren) = self.consume(Token::OpenParen) {
+            let params = self.lambda_parameters();
+            let _ = self.closing_paren(paren);
+            params
+        } else {
+            let span = self.closest_span();
+            self.at(span).parse_expected_parameters();
+            Vec::new()
+        };
+
+        let return_type = self
+            .matches(Self::TYPE_STARTS)
+            .is_some()
+            .then(|| self.parse_type());
+
+        let body = self.parse_block();
+
+        let end = self.consume(Token::End).unwrap_or_else(|| {
+            self.at(opener).parse_missing_end();
+            self.closest_span()
+        });
+
+        let lambda = Lambda {
+            params,
+            return_type,
+            body,
+        };
+
+        (ExpressionNode::Lambda(lambda), opener + end)
+    }
+
+    /// Like function parameters, except that the types may be left out, in
+    /// which case they are inferred.
+    ///
+    /// ```abnf
+    /// lambda-parameters = [lambda-names *("," lambda-names) [","]]
+    /// lambda-names      = NAME *("," NAME) [type]
+    /// ```
+    fn lambda_parameters(&mut self) -> Vec<(NamePart, Span, Option<Type>)> {
+        let mut params = Vec::new();
+        let mut untyped = 0;
+
+        while let Some((Token::ValueName(name), span)) = self.this_one() {
+            let _ = self.next();
+            let name = NamePart::new(self.db, NameNode::Value(name.clone()));
+            params.push((name, *span, None));
+            untyped += 1;
+
+            if self.matches(Self::TYPE_STARTS).is_some() {
+                let ty = self.parse_type();
+                for (_, _, param) in params.iter_mut().rev().take(untyped) {
+                    *param = Some(ty.clone());
+                }
+
+                untyped = 0;
+            }
+
+            let _ = self.consume(Token::Comma);
+        }
+
+        params
     }
 
     /// ```abnf
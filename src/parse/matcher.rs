@@ -11,6 +11,7 @@ impl Matcher for Token {
             (Self::TypeName(_), Self::TypeName(_)) => true,
             (Self::String(_), Self::String(_)) => true,
             (Self::Number(_), Self::Number(_)) => true,
+            (Self::Attribute(_), Self::Attribute(_)) => true,
 
             _ => self == token,
         }
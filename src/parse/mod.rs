@@ -1,29 +1,42 @@
 mod declarations;
 mod expressions;
 mod matcher;
+mod numbers;
+mod patterns;
 mod statements;
+mod strings;
 mod types;
 
+use std::cell::RefCell;
+use std::collections::HashSet;
+
 use self::matcher::Matcher;
 use crate::ast::Declarations;
+use crate::cst::{Event, NodeId, NodeKind, SyntaxTree};
 use crate::messages::MessageMaker;
 use crate::names::{NameNode, NamePart};
 use crate::source::{Source, Span};
 use crate::token::{lex, Token};
 use crate::Db;
 
+#[cfg(test)]
+mod tests;
+
 #[salsa::tracked]
 pub fn parse(db: &dyn Db, source: Source) -> Declarations {
     let tokens = lex(db, source);
-    let mut parser = Parser {
-        db,
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|(token, _)| !token.is_trivia())
+        .cloned()
+        .collect();
 
-        tokens: tokens.as_slice(),
-        last_span: tokens.first().map(|(_, span)| *span),
-        source,
-    };
+    let reported = RefCell::default();
+    let mut parser = Parser::new(db, source, &significant, &reported);
+    let (imports, declarations) = parser.parse_top_level();
+    let syntax = SyntaxTree::build(source, tokens, &parser.events);
 
-    parser.parse_top_level()
+    Declarations::new(db, imports, declarations, syntax)
 }
 
 pub struct Parser<'a> {
@@ -32,9 +45,97 @@ pub struct Parser<'a> {
     tokens: &'a [(Token, Span)],
     last_span: Option<Span>,
     source: Source,
+
+    /// The doc comments directly preceding the current token.
+    docs: Vec<String>,
+
+    /// The spans errors have been reported at, such that every span gets at
+    /// most one error.
+    reported: &'a RefCell<HashSet<Span>>,
+
+    /// The events from which the syntax tree is built, and the number of syntax
+    /// nodes started so far.
+    events: Vec<Event>,
+    nodes: usize,
+
+    /// Is this parser reading an interpolated expression? Its tokens are part
+    /// of a string literal, so nothing parsed from them has a syntax node.
+    interpolated: bool,
 }
 
+/// A point in the events at which a syntax node can be started later on, once
+/// it is known to be needed.
+#[derive(Clone, Copy, Debug)]
+struct Checkpoint(usize);
+
 impl<'a> Parser<'a> {
+    /// Create a parser over the given tokens, which should not contain any
+    /// trivia.
+    fn new(
+        db: &'a dyn Db,
+        source: Source,
+        tokens: &'a [(Token, Span)],
+        reported: &'a RefCell<HashSet<Span>>,
+    ) -> Self {
+        let mut parser = Self {
+            db,
+
+            tokens,
+            last_span: tokens.first().map(|(_, span)| *span),
+            source,
+
+            docs: Vec::new(),
+            reported,
+
+            events: Vec::new(),
+            nodes: 0,
+            interpolated: false,
+        };
+
+        parser.collect_docs();
+        parser
+    }
+
+    /// Start a syntax node containing every token consumed until the matching
+    /// call to [`Parser::finish_node`]. Returns the id that node will have in
+    /// the finished tree.
+    fn start_node(&mut self, kind: NodeKind) -> NodeId {
+        self.nodes += 1;
+        let id = NodeId::new(self.nodes);
+        self.events.push(Event::Start(kind, id));
+        id
+    }
+
+    fn finish_node(&mut self) {
+        self.events.push(Event::Finish);
+    }
+
+    #[must_use]
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.events.len())
+    }
+
+    /// Wrap everything parsed since `checkpoint` in a syntax node of the given
+    /// kind, such as the left-hand side of a binary expression once its
+    /// operator is found. Returns the id of the node, unless it is part of an
+    /// interpolation.
+    fn wrap(&mut self, checkpoint: Checkpoint, kind: NodeKind) -> Option<NodeId> {
+        self.nodes += 1;
+        let id = NodeId::new(self.nodes);
+        self.events.insert(checkpoint.0, Event::Start(kind, id));
+        self.finish_node();
+
+        (!self.interpolated).then_some(id)
+    }
+
+    /// Run `f` within a syntax node of the given kind.
+    fn node<T>(&mut self, kind: NodeKind, f: impl FnOnce(&mut Self) -> T) -> T {
+        let _ = self.start_node(kind);
+        let result = f(self);
+        self.finish_node();
+        result
+    }
+
     fn closest_span(&self) -> Span {
         self.tokens
             .first()
@@ -54,6 +155,10 @@ impl<'a> Parser<'a> {
             None
         } else {
             self.tokens = &self.tokens[1..];
+            self.events.push(Event::Token);
+
+            self.docs.clear();
+            self.collect_docs();
 
             let first = self.tokens.first();
             self.last_span = first.map(|(_, span)| *span).or(self.last_span);
@@ -62,6 +167,16 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Move past any doc comments, keeping them around for the declaration
+    /// that follows them.
+    fn collect_docs(&mut self) {
+        while let Some((Token::DocComment(doc), _)) = self.tokens.first() {
+            self.docs.push(doc.clone());
+            self.tokens = &self.tokens[1..];
+            self.events.push(Event::Token);
+        }
+    }
+
     fn is_done(&self) -> bool {
         self.tokens.is_empty()
     }
@@ -85,6 +200,49 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Skip past tokens until one matching `sync` or the end of the source, so
+    /// that a syntax error doesn't cause a cascade of errors from the tokens
+    /// following it. Always skips at least one token, and returns the span of
+    /// the skipped tokens, at which the caller should report an error.
+    fn recover(&mut self, sync: impl Matcher) -> Span {
+        self.node(NodeKind::Error, |this| {
+            let mut span = this.closest_span();
+            let _ = this.next();
+
+            while let Some((token, next)) = this.this_one() {
+                if sync.matches(token) {
+                    break;
+                }
+
+                span += *next;
+                let _ = this.next();
+            }
+
+            span
+        })
+    }
+
+    /// Consume the closing parenthesis of a list opened at `opener`. Anything
+    /// in the way of it is skipped, unless a declaration or statement keyword
+    /// comes first, in which case the parenthesis is reported as missing.
+    fn closing_paren(&mut self, opener: Span) -> Option<Span> {
+        if let Some(span) = self.consume(Token::CloseParen) {
+            return Some(span);
+        }
+
+        if !self.is_done() && self.matches(Self::PAREN_RECOVERY).is_none() {
+            let span = self.recover(Self::PAREN_RECOVERY);
+
+            if let Some(closer) = self.consume(Token::CloseParen) {
+                self.at(span).parse_unexpected_in_parens(opener);
+                return Some(closer);
+            }
+        }
+
+        self.at(opener).parse_missing_paren();
+        None
+    }
+
     /// Parse a name token (type or value). May produce a `NameNode::Invalid`.
     fn parse_name<T, F>(&mut self, f: F) -> (T, Span)
     where
@@ -117,6 +275,6 @@ impl<'a> Parser<'a> {
     }
 
     fn at(&self, span: Span) -> MessageMaker<'a> {
-        MessageMaker::at(self.db, span)
+        MessageMaker::at(self.db, span).once(self.reported)
     }
 }
@@ -0,0 +1,144 @@
+use super::Parser;
+use crate::ast::{ExpressionNode, NumberLiteral, NumberSuffix, NumberValue};
+use crate::source::Span;
+
+/// Ways in which a number literal can be malformed.
+enum NumberError {
+    MissingDigits,
+    InvalidDigit(char, u32),
+    UnknownSuffix(String),
+    TooLarge,
+
+    /// A fractional part in a literal of the given radix other than ten.
+    FractionalRadix(u32),
+}
+
+impl Parser<'_> {
+    /// Parse the value and suffix of a number literal. Whether the value fits
+    /// in the type of the literal is checked later, during type checking.
+    ///
+    /// ```abnf
+    /// NUMBER  = (decimal / "0x" HEX / "0b" BINARY / "0o" OCTAL) [suffix]
+    /// decimal = DIGITS ["." DIGITS] [("e" / "E") ["+" / "-"] DIGITS]
+    /// suffix  = "int" / "nat"
+    /// ```
+    pub fn number_literal(&mut self, raw: &str, span: Span) -> ExpressionNode {
+        match Self::parse_number(raw) {
+            Ok(literal) => ExpressionNode::Number(literal),
+            Err(error) => {
+                let at = self.at(span);
+                match error {
+                    NumberError::MissingDigits => at.parse_number_missing_digits(),
+                    NumberError::InvalidDigit(digit, radix) => {
+                        at.parse_number_invalid_digit(digit, radix)
+                    }
+                    NumberError::UnknownSuffix(suffix) => at.parse_number_unknown_suffix(&suffix),
+                    NumberError::TooLarge => at.parse_number_too_large(),
+                    NumberError::FractionalRadix(radix) => at.parse_number_fractional_radix(radix),
+                }
+
+                ExpressionNode::Invalid
+            }
+        }
+    }
+
+    fn parse_number(raw: &str) -> Result<NumberLiteral, NumberError> {
+        let (radix, rest) = if let Some(rest) = raw.strip_prefix("0x") {
+            (16, rest)
+        } else if let Some(rest) = raw.strip_prefix("0b") {
+            (2, rest)
+        } else if let Some(rest) = raw.strip_prefix("0o") {
+            (8, rest)
+        } else {
+            (10, raw)
+        };
+
+        let (mut mantissa, count, mut rest) = Self::digits(0, rest, radix)?;
+        if count == 0 {
+            return Err(NumberError::MissingDigits);
+        }
+
+        // `mantissa * 10^exponent` is the value of the literal
+        let mut exponent: i64 = 0;
+
+        if radix == 10 {
+            if let Some(fraction) = rest.strip_prefix('.') {
+                let (value, count, after) = Self::digits(mantissa, fraction, 10)?;
+                mantissa = value;
+                exponent -= count as i64;
+                rest = after;
+            }
+
+            if let Some(after) = rest.strip_prefix(['e', 'E']) {
+                let (negative, after) = match after.strip_prefix(['+', '-']) {
+                    Some(digits) => (after.starts_with('-'), digits),
+                    None => (false, after),
+                };
+
+                if after.starts_with(|c: char| c.is_ascii_digit()) {
+                    let (value, _, after) = Self::digits(0, after, 10)?;
+                    let value = i64::try_from(value).map_err(|_| NumberError::TooLarge)?;
+                    exponent += if negative { -value } else { value };
+                    rest = after;
+                }
+            }
+        } else if let Some(digit) = rest.chars().next().filter(char::is_ascii_digit) {
+            return Err(NumberError::InvalidDigit(digit, radix));
+        } else if rest.starts_with('.') {
+            return Err(NumberError::FractionalRadix(radix));
+        }
+
+        let suffix = match rest {
+            "" => None,
+            "int" => Some(NumberSuffix::Int),
+            "nat" => Some(NumberSuffix::Nat),
+            suffix => return Err(NumberError::UnknownSuffix(suffix.into())),
+        };
+
+        while exponent < 0 && mantissa != 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+
+        let value = if mantissa == 0 {
+            NumberValue::Integer(0)
+        } else if exponent >= 0 {
+            u32::try_from(exponent)
+                .ok()
+                .and_then(|exponent| 10u128.checked_pow(exponent))
+                .and_then(|scale| mantissa.checked_mul(scale))
+                .map(NumberValue::Integer)
+                .ok_or(NumberError::TooLarge)?
+        } else {
+            let exponent = i32::try_from(exponent).map_err(|_| NumberError::TooLarge)?;
+            NumberValue::Fraction { mantissa, exponent }
+        };
+
+        Ok(NumberLiteral { value, suffix })
+    }
+
+    /// Accumulate the digits at the start of `text` onto `value`, skipping any
+    /// `_` separators. Returns the new value, the number of digits read and the
+    /// remaining text.
+    fn digits(mut value: u128, text: &str, radix: u32) -> Result<(u128, usize, &str), NumberError> {
+        let mut count = 0;
+
+        for (i, c) in text.char_indices() {
+            if c == '_' {
+                continue;
+            }
+
+            let Some(digit) = c.to_digit(radix) else {
+                return Ok((value, count, &text[i..]));
+            };
+
+            value = value
+                .checked_mul(u128::from(radix))
+                .and_then(|value| value.checked_add(u128::from(digit)))
+                .ok_or(NumberError::TooLarge)?;
+            count += 1;
+        }
+
+        Ok((value, count, ""))
+    }
+}
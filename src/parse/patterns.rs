@@ -0,0 +1,114 @@
+use super::Parser;
+use crate::ast::{Pattern, PatternNode, Type, TypeNode};
+use crate::cst::NodeKind;
+use crate::names::{NameNode, NamePart};
+use crate::source::Span;
+use crate::token::Token;
+
+impl Parser<'_> {
+    pub const PATTERN_STARTS: &[Token] = &[
+        Token::ValueName(String::new()),
+        Token::TypeName(String::new()),
+    ];
+
+    /// ```abnf
+    /// pattern  = VALUE_NAME
+    /// pattern =/ class-path ["(" pattern-list ")"]
+    /// ```
+    pub fn parse_pattern(&mut self) -> Pattern {
+        let checkpoint = self.checkpoint();
+        let (node, span) = self.pattern();
+        let syntax = self.wrap(checkpoint, NodeKind::Pattern);
+
+        Pattern { node, span, syntax }
+    }
+
+    fn pattern(&mut self) -> (PatternNode, Span) {
+        let (node, span) = match self.this_one() {
+            Some((Token::ValueName(name), span)) => {
+                let _ = self.next();
+                let name = NamePart::new(self.db, NameNode::Value(name.clone()));
+                (PatternNode::Binding(name), *span)
+            }
+
+            Some((Token::TypeName(_), _)) => {
+                let class = self.class_path();
+                let mut span = class.span;
+
+                let fields = if let Some(opener) = self.consume(Token::OpenParen) {
+                    let fields = self.pattern_list();
+                    span += self
+                        .closing_paren(opener)
+                        .unwrap_or_else(|| self.closest_span());
+
+                    fields
+                } else {
+                    Vec::new()
+                };
+
+                (PatternNode::Class(class, fields), span)
+            }
+
+            Some((_, span)) => {
+                self.at(*span).parse_expected_pattern();
+                (PatternNode::Invalid, *span)
+            }
+
+            None => {
+                let span = self.closest_span();
+                self.at(span).parse_expected_pattern();
+                (PatternNode::Invalid, span)
+            }
+        };
+
+        (node, span)
+    }
+
+    /// ```abnf
+    /// class-path = TYPE_NAME *("." TYPE_NAME)
+    /// ```
+    fn class_path(&mut self) -> Type {
+        let checkpoint = self.checkpoint();
+        let (node, span) = self.parse_name(|_, name, _| TypeNode::Name(name));
+        let syntax = self.wrap(checkpoint, NodeKind::Type);
+        let mut path = Type { node, span, syntax };
+
+        while self.consume(Token::Dot).is_some() {
+            let span = path.span;
+            let (node, closer) = self.parse_name(|this, name, span| {
+                match name.node(this.db) {
+                    NameNode::Type(..) => {}
+                    NameNode::Value(name) => {
+                        this.at(span).parse_expected_type_name(Some(name.as_str()))
+                    }
+                    NameNode::Quoted(_) | NameNode::Invalid => {
+                        this.at(span).parse_expected_type_name(None)
+                    }
+                }
+
+                TypeNode::Field(Box::new(path), name)
+            });
+
+            let span = span + closer;
+            let syntax = self.wrap(checkpoint, NodeKind::Type);
+            path = Type { node, span, syntax };
+        }
+
+        path
+    }
+
+    /// ```abnf
+    /// pattern-list = [pattern *("," pattern) [","]]
+    /// ```
+    fn pattern_list(&mut self) -> Vec<Pattern> {
+        let mut patterns = Vec::new();
+
+        while self.matches(Self::PATTERN_STARTS).is_some() {
+            patterns.push(self.parse_pattern());
+
+            let _ = self.consume(Token::Comma);
+        }
+
+        patterns
+    }
+}
@@ -1,10 +1,18 @@
 use super::Parser;
-use crate::ast::{Block, Expression, ExpressionNode, Statement, StatementNode};
+use crate::ast::{Block, CaseArm, Expression, ExpressionNode, Statement, StatementNode};
+use crate::cst::NodeKind;
 use crate::names::NameNode;
+use crate::source::Span;
 use crate::token::Token;
 
 impl Parser<'_> {
     pub const STATEMENT_START: &[Token] = &[
+        Token::Break,
+        Token::Case,
+        Token::Continue,
+        Token::If,
+        Token::Loop,
+        Token::While,
         Token::Null,
         Token::Return,
         Token::Let,
@@ -12,27 +20,125 @@ impl Parser<'_> {
         Token::ValueName(String::new()),
         Token::Number(String::new()),
         Token::String(String::new()),
+        Token::TypeName(String::new()),
+        Token::This,
+        Token::Minus,
         Token::OpenParen,
     ];
 
+    /// The tokens which end a block without beginning a statement. Blocks also
+    /// end at the start of a declaration, in case an `end` is missing.
+    pub const BLOCK_END: &[Token] = &[Token::End, Token::Else, Token::Is];
+
+    /// The tokens at which a block resumes after an error.
+    pub const STATEMENT_RECOVERY: &[Token] = &[
+        Token::Break,
+        Token::Case,
+        Token::Continue,
+        Token::If,
+        Token::Loop,
+        Token::While,
+        Token::Null,
+        Token::Return,
+        Token::Let,
+        Token::Var,
+        Token::ValueName(String::new()),
+        Token::Number(String::new()),
+        Token::String(String::new()),
+        Token::TypeName(String::new()),
+        Token::This,
+        Token::Minus,
+        Token::OpenParen,
+        Token::End,
+        Token::Else,
+        Token::Is,
+        Token::Private,
+        Token::Class,
+        Token::Function,
+        Token::Variant,
+    ];
+
+    /// The tokens at which a parenthesized list gives up on finding its closing
+    /// parenthesis.
+    pub const PAREN_RECOVERY: &[Token] = &[
+        Token::CloseParen,
+        Token::Break,
+        Token::Case,
+        Token::Continue,
+        Token::If,
+        Token::Loop,
+        Token::While,
+        Token::Null,
+        Token::Return,
+        Token::Let,
+        Token::Var,
+        Token::End,
+        Token::Else,
+        Token::Is,
+        Token::Private,
+        Token::Class,
+        Token::Function,
+        Token::Variant,
+    ];
+
     pub fn parse_block(&mut self) -> Block {
-        let mut statements = Vec::new();
+        self.node(NodeKind::Block, |this| {
+            let mut statements = Vec::new();
 
-        while self.matches(Self::STATEMENT_START).is_some() {
-            statements.push(self.statement());
-        }
+            loop {
+                if this.at_statement() {
+                    statements.push(this.statement());
+                } else if this.is_done()
+                    || this.matches(Self::BLOCK_END).is_some()
+                    || this.at_declaration()
+                {
+                    break;
+                } else {
+                    let span = this.recover(Self::STATEMENT_RECOVERY);
+                    this.at(span).parse_expected_statement();
+                }
+            }
 
-        Block(statements)
+            Block(statements)
+        })
+    }
+
+    /// Is the parser at the start of a statement? Besides the usual tokens,
+    /// this includes statements beginning with a lambda, like
+    /// `function(x Int) Unit ... end(5)`.
+    pub fn at_statement(&self) -> bool {
+        self.matches(Self::STATEMENT_START).is_some() || self.at_lambda()
+    }
+
+    /// Is the parser at the start of a declaration, which is a `function` not
+    /// followed by a parenthesis or another declaration keyword?
+    fn at_declaration(&self) -> bool {
+        let keyword = self.matches(Self::DECLARATION_START).is_some()
+            || self.matches(Token::Private).is_some();
+
+        keyword && !self.at_lambda()
+    }
+
+    fn at_lambda(&self) -> bool {
+        matches!(
+            self.tokens,
+            [(Token::Function, _), (Token::OpenParen, _), ..]
+        )
     }
 
     fn statement(&mut self) -> Statement {
+        let checkpoint = self.checkpoint();
+        let (node, span) = self.statement_node();
+        let syntax = self.wrap(checkpoint, NodeKind::Statement);
+
+        Statement { node, span, syntax }
+    }
+
+    fn statement_node(&mut self) -> (StatementNode, Span) {
         match self.this_one() {
             Some((Token::Null, span)) => {
                 let _ = self.next();
-                Statement {
-                    node: StatementNode::Null,
-                    span: *span,
-                }
+                (StatementNode::Null, *span)
             }
 
             Some((Token::Return, opener)) => {
@@ -44,14 +150,12 @@ impl Parser<'_> {
                     .unwrap_or(Expression {
                         node: ExpressionNode::Unit,
                         span: *opener,
+                        syntax: None,
                     });
 
                 let span = *opener + expr.span;
 
-                Statement {
-                    node: StatementNode::Return(expr),
-                    span,
-                }
+                (StatementNode::Return(expr), span)
             }
 
             Some((Token::Let, opener)) => {
@@ -63,7 +167,9 @@ impl Parser<'_> {
                             this.at(span).parse_expected_value_name(Some(name.as_str()))
                         }
 
-                        NameNode::Invalid => this.at(span).parse_expected_value_name(None),
+                        NameNode::Quoted(_) | NameNode::Invalid => {
+                            this.at(span).parse_expected_value_name(None)
+                        }
                     }
                     name
                 });
@@ -80,10 +186,7 @@ impl Parser<'_> {
 
                 let span = *opener + body.span;
 
-                Statement {
-                    node: StatementNode::Constant(name, ty, body),
-                    span,
-                }
+                (StatementNode::Constant(name, ty, body), span)
             }
 
             Some((Token::Var, opener)) => {
@@ -94,7 +197,9 @@ impl Parser<'_> {
                         NameNode::Type(name) => {
                             this.at(span).parse_expected_value_name(Some(name.as_str()))
                         }
-                        NameNode::Invalid => this.at(span).parse_expected_value_name(None),
+                        NameNode::Quoted(_) | NameNode::Invalid => {
+                            this.at(span).parse_expected_value_name(None)
+                        }
                     }
 
                     name
@@ -112,10 +217,72 @@ impl Parser<'_> {
 
                 let span = *opener + body.span;
 
-                Statement {
-                    node: StatementNode::Variable(name, ty, body),
-                    span,
+                (StatementNode::Variable(name, ty, body), span)
+            }
+
+            Some((Token::Break, span)) => {
+                let _ = self.next();
+                (StatementNode::Break, *span)
+            }
+
+            Some((Token::Continue, span)) => {
+                let _ = self.next();
+                (StatementNode::Continue, *span)
+            }
+
+            Some((Token::If, opener)) => {
+                let (node, span) = self.if_chain();
+
+                let end = self.consume(Token::End).unwrap_or_else(|| {
+                    self.at(*opener).parse_missing_end();
+                    self.closest_span()
+                });
+
+                (node, span + end)
+            }
+
+            Some((Token::While, opener)) => {
+                let _ = self.next();
+                let cond = self.parse_expression();
+                let body = self.parse_block();
+
+                let end = self.consume(Token::End).unwrap_or_else(|| {
+                    self.at(*opener).parse_missing_end();
+                    self.closest_span()
+                });
+
+                (StatementNode::While(cond, body), *opener + end)
+            }
+
+            Some((Token::Loop, opener)) => {
+                let _ = self.next();
+                let body = self.parse_block();
+
+                let end = self.consume(Token::End).unwrap_or_else(|| {
+                    self.at(*opener).parse_missing_end();
+                    self.closest_span()
+                });
+
+                (StatementNode::Loop(body), *opener + end)
+            }
+
+            Some((Token::Case, opener)) => {
+                let _ = self.next();
+                let scrutinee = self.parse_expression();
+
+                let mut arms = Vec::new();
+                while self.matches(Token::Is).is_some() {
+                    arms.push(self.case_arm());
                 }
+
+                let end = self.consume(Token::End).unwrap_or_else(|| {
+                    self.at(*opener).parse_missing_end();
+                    self.closest_span()
+                });
+
+                let span = *opener + end;
+
+                (StatementNode::Case(scrutinee, arms), span)
             }
 
             Some(_) => {
@@ -127,7 +294,54 @@ impl Parser<'_> {
         }
     }
 
-    fn expression_or_assignment(&mut self, expr: Expression) -> Statement {
+    /// Parse an `if` statement along with any `else if` and `else` blocks, but
+    /// not the closing `end`, which is shared by the entire chain.
+    ///
+    /// ```abnf
+    /// if-chain = "if" expr *statement ["else" (if-chain / *statement)]
+    /// ```
+    fn if_chain(&mut self) -> (StatementNode, Span) {
+        let opener = self.consume(Token::If).expect("if chains begin with 'if'");
+        let cond = self.parse_expression();
+        let then = self.parse_block();
+
+        let otherwise = self.consume(Token::Else).map(|_| {
+            if self.matches(Token::If).is_some() {
+                let checkpoint = self.checkpoint();
+                let (node, span) = self.if_chain();
+                let syntax = self.wrap(checkpoint, NodeKind::Statement);
+
+                Block(vec![Statement { node, span, syntax }])
+            } else {
+                self.parse_block()
+            }
+        });
+
+        let span = opener + self.closest_span();
+        (StatementNode::If(cond, then, otherwise), span)
+    }
+
+    /// ```abnf
+    /// case-arm = "is" pattern block
+    /// ```
+    fn case_arm(&mut self) -> CaseArm {
+        let opener = self.consume(Token::Is).expect("case arms begin with 'is'");
+        let pattern = self.parse_pattern();
+        let body = self.parse_block();
+
+        let end = self.consume(Token::End).unwrap_or_else(|| {
+            self.at(opener).parse_missing_end();
+            self.closest_span()
+        });
+
+        CaseArm {
+            pattern,
+            body,
+            span: opener + end,
+        }
+    }
+
+    fn expression_or_assignment(&mut self, expr: Expression) -> (StatementNode, Span) {
         let mut span = expr.span;
         let node = if self.consume(Token::ColonEqual).is_some() {
             let body = self.parse_expression();
@@ -137,6 +351,6 @@ impl Parser<'_> {
             StatementNode::Expression(expr)
         };
 
-        Statement { node, span }
+        (node, span)
     }
 }
@@ -0,0 +1,164 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use logos::Logos;
+
+use super::Parser;
+use crate::ast::{Expression, ExpressionNode, StringPart};
+use crate::source::Span;
+use crate::token::Token;
+
+impl Parser<'_> {
+    /// Decode the contents of a string literal, parsing any interpolated
+    /// expressions within it. `span` is the span of the entire literal,
+    /// including its quotes.
+    pub fn string_literal(&mut self, raw: &str, span: Span) -> ExpressionNode {
+        let mut parts = self.string_parts(raw, span);
+
+        match parts.as_mut_slice() {
+            [StringPart::Text(text)] => ExpressionNode::String(std::mem::take(text)),
+            _ => ExpressionNode::Interpolated(parts),
+        }
+    }
+
+    /// Decode the contents of a string which may not contain interpolations,
+    /// such as a quoted name.
+    pub fn plain_string(&mut self, raw: &str, span: Span) -> String {
+        let mut result = String::new();
+
+        for part in self.string_parts(raw, span) {
+            match part {
+                StringPart::Text(text) => result.push_str(&text),
+                StringPart::Expression(expr) => self.at(expr.span).parse_unexpected_interpolation(),
+            }
+        }
+
+        result
+    }
+
+    /// Split a string into its literal text and interpolated expressions,
+    /// decoding escape sequences along the way. Always produces at least one
+    /// part.
+    fn string_parts(&mut self, raw: &str, span: Span) -> Vec<StringPart> {
+        // Skip the opening quote
+        let base = span.start + 1;
+        let at = |start: usize, end: usize| Span::new(span.source, base + start, base + end);
+
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = raw.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, 'n')) => Some('\n'),
+                        Some((_, 't')) => Some('\t'),
+                        Some((_, '\\')) => Some('\\'),
+                        Some((_, '"')) => Some('"'),
+                        Some((_, '{')) => Some('{'),
+                        Some((_, '}')) => Some('}'),
+                        Some((_, 'u')) => Self::unicode_escape(&mut chars),
+                        _ => None,
+                    };
+
+                    if let Some(c) = escaped {
+                        text.push(c);
+                    } else {
+                        let end = chars.peek().map(|(j, _)| *j).unwrap_or(raw.len());
+                        self.at(at(i, end)).parse_invalid_escape();
+                    }
+                }
+
+                '{' => {
+                    let start = i + 1;
+                    let Some(length) = raw[start..].find('}') else {
+                        self.at(at(i, raw.len())).parse_unclosed_interpolation();
+                        break;
+                    };
+
+                    let end = start + length;
+                    while chars.next_if(|(j, _)| *j <= end).is_some() {}
+
+                    if !text.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut text)));
+                    }
+
+                    let expr = self.interpolation(&raw[start..end], base + start);
+                    parts.push(StringPart::Expression(expr));
+                }
+
+                '}' => {
+                    self.at(at(i, i + 1)).parse_unmatched_brace();
+                }
+
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() || parts.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+
+        parts
+    }
+
+    /// Decode the `{...}` part of a `\u{...}` escape, which should contain one
+    /// to six hexadecimal digits. On failure, this skips past the braces so
+    /// that the rest of the escape isn't treated as text.
+    fn unicode_escape(chars: &mut Peekable<CharIndices>) -> Option<char> {
+        chars.next_if(|(_, c)| *c == '{')?;
+
+        // `None` once the value no longer fits, which only happens well past
+        // the six digits allowed.
+        let mut value = Some(0u32);
+        let mut digits = 0;
+
+        while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_hexdigit()) {
+            let digit = c.to_digit(16).expect("checked above");
+            value = value
+                .and_then(|value| value.checked_mul(16))
+                .and_then(|value| value.checked_add(digit));
+            digits += 1;
+        }
+
+        let closed = chars.next_if(|(_, c)| *c == '}').is_some();
+        if !closed {
+            while chars.next_if(|(_, c)| c.is_alphanumeric()).is_some() {}
+            let _ = chars.next_if(|(_, c)| *c == '}');
+            return None;
+        }
+
+        if (1..=6).contains(&digits) {
+            value.and_then(char::from_u32)
+        } else {
+            None
+        }
+    }
+
+    /// Parse the code within an interpolation `{...}` as an expression.
+    /// `offset` is where the code begins in the source.
+    fn interpolation(&mut self, code: &str, offset: usize) -> Expression {
+        let span = Span::new(self.source, offset, offset + code.len());
+        let tokens: Vec<_> = Token::lexer(code)
+            .spanned()
+            .filter(|(token, _)| !token.is_trivia())
+            .map(|(token, range)| {
+                let span = Span::new(self.source, offset + range.start, offset + range.end);
+                (token, span)
+            })
+            .collect();
+
+        let mut parser = Parser::new(self.db, self.source, &tokens, self.reported);
+        parser.last_span = parser.last_span.or(Some(span));
+        parser.interpolated = true;
+
+        let expr = parser.parse_expression();
+
+        if let Some((_, span)) = parser.this_one() {
+            parser.at(*span).parse_expected_interpolation_end();
+        }
+
+        expr
+    }
+}
@@ -0,0 +1,763 @@
+use crate::ast::{
+    Block, DeclarationNode, Expression, ExpressionNode, NumberLiteral, NumberSuffix, NumberValue,
+    PatternNode, StatementNode, StringPart, TypeNode,
+};
+use crate::cst::{NodeId, NodeKind};
+use crate::messages::LabelKind;
+use crate::source::{Lints, Source};
+use crate::token::{lex, Token};
+use crate::Messages;
+
+use super::parse;
+
+#[derive(Default)]
+#[salsa::db(crate::Jar)]
+struct Database {
+    storage: salsa::Storage<Self>,
+}
+
+impl salsa::Database for Database {}
+
+#[test]
+fn syntax_tree_is_lossless() {
+    let db = Database::default();
+    let text = "\
+-- a comment
+# another comment
+--- Documentation.
+function f(x Int) Int  {- block {- nested -} comment -}
+    return x + 1  -- trailing
+end
+
+class A ? is B
+    var y Int := \"string {x}\"
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let syntax = parse(&db, source).syntax(&db);
+
+    assert_eq!(syntax.text(&db, syntax.root()), text);
+}
+
+#[test]
+fn declarations_refer_to_their_syntax() {
+    let db = Database::default();
+    let text = "\
+--- Documentation.
+function f() Int
+    return 1
+end  -- trailing
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let declarations = parse(&db, source);
+    let syntax = declarations.syntax(&db);
+
+    let [declaration] = declarations.declarations(&db).as_slice() else {
+        panic!("expected exactly one declaration");
+    };
+
+    let node = declaration.syntax(&db);
+    assert_eq!(syntax.node(node).kind, NodeKind::Declaration);
+    assert_eq!(
+        syntax.text(&db, node),
+        "function f() Int\n    return 1\nend"
+    );
+}
+
+#[test]
+fn ast_nodes_refer_to_their_syntax() {
+    let db = Database::default();
+    let text = "\
+function f(x Int) List(Int)
+    let y Int := -(x + 1) * 2
+    case y
+        is Some(z)
+            return \"{z}\"
+        end
+    end
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let declarations = parse(&db, source);
+    let syntax = declarations.syntax(&db);
+    let text = |id: Option<NodeId>| syntax.text(&db, id.expect("expected a syntax node"));
+
+    let DeclarationNode::Function {
+        return_type,
+        body: Some(Block(body)),
+        ..
+    } = declarations.declarations(&db)[0].node(&db)
+    else {
+        panic!("expected a function with a body");
+    };
+
+    let TypeNode::Applied(head, _) = &return_type.node else {
+        panic!("expected an applied type");
+    };
+
+    assert_eq!(text(return_type.syntax), "List(Int)");
+    assert_eq!(text(head.syntax), "List");
+    assert_eq!(syntax.node(head.syntax.unwrap()).kind, NodeKind::Type);
+
+    let [constant, case] = body.as_slice() else {
+        panic!("expected two statements");
+    };
+
+    let StatementNode::Constant(_, ty, value) = &constant.node else {
+        panic!("expected a constant");
+    };
+
+    let ExpressionNode::Binary(_, lhs, rhs) = &value.node else {
+        panic!("expected a binary expression");
+    };
+
+    let ExpressionNode::Unary(_, operand) = &lhs.node else {
+        panic!("expected a unary expression");
+    };
+
+    assert_eq!(text(constant.syntax), "let y Int := -(x + 1) * 2");
+    assert_eq!(text(ty.syntax), "Int");
+    assert_eq!(text(value.syntax), "-(x + 1) * 2");
+    assert_eq!(text(lhs.syntax), "-(x + 1)");
+    assert_eq!(text(operand.syntax), "x + 1");
+    assert_eq!(text(rhs.syntax), "2");
+
+    let StatementNode::Case(_, arms) = &case.node else {
+        panic!("expected a case statement");
+    };
+
+    let PatternNode::Class(class, fields) = &arms[0].pattern.node else {
+        panic!("expected a class pattern");
+    };
+
+    assert_eq!(text(arms[0].pattern.syntax), "Some(z)");
+    assert_eq!(text(class.syntax), "Some");
+    assert_eq!(text(fields[0].syntax), "z");
+
+    // Interpolated code is part of the string token, so has no node of its own
+    let StatementNode::Return(string) = &arms[0].body.0[0].node else {
+        panic!("expected a return statement");
+    };
+
+    let ExpressionNode::Interpolated(parts) = &string.node else {
+        panic!("expected an interpolated string");
+    };
+
+    assert_eq!(text(string.syntax), "\"{z}\"");
+    assert!(matches!(&parts[0], StringPart::Expression(expr) if expr.syntax.is_none()));
+}
+
+#[test]
+fn syntax_nodes_map_to_declarations() {
+    let db = Database::default();
+    let text = "\
+class A
+    function f(this) Int
+        return 1
+    end
+
+    private
+    var x Int
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let declarations = parse(&db, source);
+    let syntax = declarations.syntax(&db);
+
+    let enclosing = |needle: &str| {
+        let node = syntax.node_at(text.find(needle).unwrap());
+        let declaration = declarations.enclosing(&db, node)?;
+        Some(syntax.text(&db, declaration.syntax(&db)))
+    };
+
+    assert_eq!(
+        enclosing("return").unwrap(),
+        "function f(this) Int\n        return 1\n    end"
+    );
+    assert_eq!(enclosing("var").unwrap(), "var x Int");
+    assert_eq!(enclosing("private").unwrap(), text.trim_end());
+
+    // The final newline is outside of every declaration
+    let root = syntax.node_at(text.len() - 1);
+    assert_eq!(root, syntax.root());
+    assert_eq!(declarations.enclosing(&db, root), None);
+}
+
+/// Parse the text and render every message as `code start..end: message`, one
+/// per line.
+fn messages(text: &str) -> String {
+    let db = Database::default();
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+
+    parse::accumulated::<Messages>(&db, source)
+        .into_iter()
+        .map(|message| {
+            let span = message
+                .labels
+                .iter()
+                .find(|label| label.kind == LabelKind::Primary)
+                .map(|label| label.at)
+                .expect("every message has a primary label");
+
+            format!(
+                "{} {}..{}: {}\n",
+                message.code.unwrap_or_default(),
+                span.start,
+                span.end,
+                message.message.unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn recover_from_stray_tokens_in_class() {
+    let text = "\
+class A
+    ) ] :=
+    function f() Int
+        return 1
+    end
+end
+";
+
+    assert_eq!(messages(text), "EP00 12..18: expected a declaration\n");
+}
+
+#[test]
+fn recover_from_stray_tokens_at_top_level() {
+    let text = "\
+) end
+function f() Int
+    return 1
+end
+";
+
+    assert_eq!(messages(text), "EP00 0..5: expected a declaration\n");
+}
+
+#[test]
+fn recover_from_stray_tokens_in_block() {
+    let text = "\
+function f() Int
+    let x Int := 1
+    ] ]
+    return x
+end
+";
+
+    assert_eq!(messages(text), "EP04 40..43: expected a statement\n");
+}
+
+#[test]
+fn recover_from_missing_end() {
+    let text = "\
+function f() Int
+    return 1
+
+function g() Int
+    return 2
+end
+";
+
+    assert_eq!(messages(text), "EP20 0..8: missing an 'end' keyword\n");
+}
+
+#[test]
+fn recover_within_parentheses() {
+    let text = "\
+function f() Int
+    return g(1 ] 2)
+end
+";
+
+    assert_eq!(
+        messages(text),
+        "EP22 32..35: unexpected tokens before closing parenthesis\n"
+    );
+}
+
+#[test]
+fn one_error_per_span() {
+    let text = "\
+function f() Int
+    let x Int := )
+    return x
+end
+";
+
+    assert_eq!(messages(text), "EP01 34..35: expected an expression\n");
+}
+
+/// Parse the text as the body of a function, which should parse without any
+/// errors.
+fn body(text: &str) -> Block {
+    let db = Database::default();
+    let text = format!("function f() Int\n{text}\nend\n");
+    let source = Source::new(&db, text, String::new(), Lints::default());
+
+    let declarations = parse(&db, source);
+    assert_eq!(parse::accumulated::<Messages>(&db, source), []);
+
+    match declarations.declarations(&db)[0].node(&db) {
+        DeclarationNode::Function {
+            body: Some(body), ..
+        } => body.clone(),
+        _ => panic!("expected a function with a body"),
+    }
+}
+
+/// Render an expression with every operation in parentheses, and every other
+/// expression as a placeholder.
+fn render(expr: &Expression) -> String {
+    match &expr.node {
+        ExpressionNode::Binary(op, lhs, rhs) => {
+            format!("({} {} {})", render(lhs), op.function_name(), render(rhs))
+        }
+
+        ExpressionNode::Unary(op, operand) => {
+            format!("({} {})", op.function_name(), render(operand))
+        }
+
+        ExpressionNode::Name(_) => "x".into(),
+        ExpressionNode::Number(_) => "1".into(),
+        node => panic!("unexpected expression {node:?}"),
+    }
+}
+
+/// Parse the returned expression, and render it with every operation in
+/// parentheses.
+fn grouped(text: &str) -> String {
+    let block = body(&format!("    return {text}"));
+    match &block.0[..] {
+        [statement] => match &statement.node {
+            StatementNode::Return(expr) => render(expr),
+            _ => panic!("expected a return statement"),
+        },
+
+        _ => panic!("expected a single statement"),
+    }
+}
+
+#[test]
+fn operator_precedence() {
+    assert_eq!(grouped("x + 1 * x"), "(x + (1 * x))");
+    assert_eq!(grouped("x * 1 + x"), "((x * 1) + x)");
+    assert_eq!(grouped("x + 1 < x * 1"), "((x + 1) < (x * 1))");
+    assert_eq!(grouped("-x * 1"), "((neg x) * 1)");
+    assert_eq!(grouped("x - -1"), "(x - (neg 1))");
+}
+
+#[test]
+fn operator_associativity() {
+    assert_eq!(grouped("x - 1 - x"), "((x - 1) - x)");
+    assert_eq!(grouped("x / 1 * x"), "((x / 1) * x)");
+    assert_eq!(grouped("- - x"), "(neg (neg x))");
+}
+
+#[test]
+fn chained_comparisons() {
+    let text = "\
+function f() Int
+    return 1 < 2 < 3
+end
+";
+
+    assert_eq!(
+        messages(text),
+        "EP30 34..35: comparison operators cannot be chained\n"
+    );
+}
+
+/// Render the control flow of a block, leaving out expressions.
+fn outline(block: &Block) -> String {
+    let statements: Vec<_> = block
+        .0
+        .iter()
+        .map(|statement| match &statement.node {
+            StatementNode::If(_, then, None) => format!("if {}", outline(then)),
+            StatementNode::If(_, then, Some(otherwise)) => {
+                format!("if {} else {}", outline(then), outline(otherwise))
+            }
+
+            StatementNode::While(_, body) => format!("while {}", outline(body)),
+            StatementNode::Loop(body) => format!("loop {}", outline(body)),
+            StatementNode::Break => "break".into(),
+            StatementNode::Continue => "continue".into(),
+            StatementNode::Return(_) => "return".into(),
+            StatementNode::Expression(_) => "expr".into(),
+            node => panic!("unexpected statement {node:?}"),
+        })
+        .collect();
+
+    format!("[{}]", statements.join(", "))
+}
+
+#[test]
+fn control_flow_statements() {
+    let block = body(
+        "\
+    if x < 1
+        x
+    else if x < 2
+        return 1
+    else
+        x
+        x
+    end
+
+    while x < 3
+        if x = 2
+            continue
+        end
+
+        break
+    end
+
+    loop
+        loop
+            break
+        end
+    end",
+    );
+
+    assert_eq!(
+        outline(&block),
+        "[if [expr] else [if [return] else [expr, expr]], \
+         while [if [continue], break], \
+         loop [loop [break]]]"
+    );
+}
+
+#[test]
+fn statements_starting_with_operators() {
+    // A minus following an expression is a subtraction instead
+    let block = body("    -x\n    loop\n        break\n    end\n    - -x - 1");
+    assert_eq!(outline(&block), "[expr, loop [break], expr]");
+}
+
+#[test]
+fn statements_starting_with_lambdas() {
+    let block = body("    function(x Int) Unit\n        x\n    end(5)\n    return 1");
+    assert_eq!(outline(&block), "[expr, return]");
+
+    let text = "\
+function f() Unit
+    function(x Int) Unit
+        x
+    end(5)
+end
+";
+
+    assert_eq!(messages(text), "");
+}
+
+#[test]
+fn recover_from_missing_end_in_loop() {
+    let text = "\
+function f() Int
+    while 1 < 2
+        break
+    return 1
+end
+";
+
+    // The `end` meant for the function closes the loop instead
+    assert_eq!(messages(text), "EP20 0..8: missing an 'end' keyword\n");
+}
+
+#[test]
+fn lex_comments() {
+    let db = Database::default();
+    let text = "\
+-- line comment
+---- also a line comment
+# hash comment
+--- Doc comment.
+{- block {- nested -} still -} x
+{--}y -- trailing
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let tokens: Vec<_> = lex(&db, source)
+        .iter()
+        .filter(|(token, _)| !token.is_trivia())
+        .map(|(token, span)| (token.clone(), &text[span.start..span.end]))
+        .collect();
+
+    assert_eq!(
+        tokens,
+        [
+            (Token::DocComment("Doc comment.".into()), "--- Doc comment."),
+            (Token::ValueName("x".into()), "x"),
+            (Token::ValueName("y".into()), "y"),
+        ]
+    );
+
+    let comments: Vec<_> = lex(&db, source)
+        .iter()
+        .filter(|(token, _)| *token == Token::Comment)
+        .map(|(_, span)| &text[span.start..span.end])
+        .collect();
+
+    assert_eq!(
+        comments,
+        [
+            "-- line comment",
+            "---- also a line comment",
+            "# hash comment",
+            "{- block {- nested -} still -}",
+            "{--}",
+            "-- trailing",
+        ]
+    );
+
+    assert_eq!(lex::accumulated::<Messages>(&db, source), []);
+}
+
+#[test]
+fn doc_comments_attach_to_declarations() {
+    let db = Database::default();
+    let text = "\
+--- First line.
+---
+---Third line.
+function f() Int
+    return 1
+end
+
+--- Documents `g`.
+function g() Int
+    return 2
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let declarations = parse(&db, source);
+    let [f, g] = declarations.declarations(&db).as_slice() else {
+        panic!("expected two declarations");
+    };
+
+    assert_eq!(f.docs(&db), &["First line.", "", "Third line."]);
+    assert_eq!(g.docs(&db), &["Documents `g`."]);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let text = "\
+function f() Int
+    return 1
+end
+{- outer {- inner -}
+";
+
+    assert_eq!(messages(text), "EL00 34..36: unterminated block comment\n");
+}
+
+/// Parse the returned expression.
+fn returned(text: &str) -> ExpressionNode {
+    let block = body(&format!("    return {text}"));
+    match &block.0[..] {
+        [statement] => match &statement.node {
+            StatementNode::Return(expr) => expr.node.clone(),
+            _ => panic!("expected a return statement"),
+        },
+
+        _ => panic!("expected a single statement"),
+    }
+}
+
+/// Wrap the text in a function returning it, and find the messages about it.
+fn string_messages(text: &str) -> String {
+    messages(&format!("function f() String\n    return {text}\nend\n"))
+}
+
+#[test]
+fn valid_escapes() {
+    assert_eq!(
+        returned(r#""\n\t\\\"\{\}""#),
+        ExpressionNode::String("\n\t\\\"{}".into())
+    );
+
+    assert_eq!(
+        returned(r#""\u{41}\u{e9}\u{1F600}\u{10FFFF}""#),
+        ExpressionNode::String("A\u{e9}\u{1F600}\u{10FFFF}".into())
+    );
+}
+
+#[test]
+fn invalid_escapes() {
+    assert_eq!(
+        string_messages(r#""\q""#),
+        "EP40 32..34: invalid escape sequence\n"
+    );
+
+    assert_eq!(
+        string_messages(r#""\u{}\u{D800}\u{110000}\u{12""#),
+        "EP40 32..36: invalid escape sequence\n\
+         EP40 36..44: invalid escape sequence\n\
+         EP40 44..54: invalid escape sequence\n\
+         EP40 54..59: invalid escape sequence\n"
+    );
+}
+
+#[test]
+fn overlong_escapes() {
+    // Neither overflows while decoding
+    assert_eq!(
+        string_messages(r#""\u{1234567} \u{FFFFFFFFFFFFFFFFFFFF}""#),
+        "EP40 32..43: invalid escape sequence\n\
+         EP40 44..68: invalid escape sequence\n"
+    );
+}
+
+#[test]
+fn interpolation_parts() {
+    let ExpressionNode::Interpolated(parts) = returned(r#""a {x} b {y + 1}{z}""#) else {
+        panic!("expected an interpolated string");
+    };
+
+    let parts: Vec<_> = parts
+        .iter()
+        .map(|part| match part {
+            StringPart::Text(text) => text.clone(),
+            StringPart::Expression(expr) => format!("{{{}}}", render(expr)),
+        })
+        .collect();
+
+    assert_eq!(parts, ["a ", "{x}", " b ", "{(x + 1)}", "{x}"]);
+}
+
+#[test]
+fn nested_interpolation_braces() {
+    // The interpolation ends at the first '}'
+    assert_eq!(
+        string_messages(r#""{ {x} }""#),
+        "EP01 34..35: expected an expression\n\
+         EP42 38..39: unmatched '}' in string\n"
+    );
+
+    assert_eq!(
+        string_messages(r#""{x""#),
+        "EP41 32..34: unclosed interpolation\n"
+    );
+}
+
+/// Parse the returned number literal.
+fn number(text: &str) -> (NumberValue, Option<NumberSuffix>) {
+    match returned(text) {
+        ExpressionNode::Number(NumberLiteral { value, suffix }) => (value, suffix),
+        node => panic!("expected a number literal, found {node:?}"),
+    }
+}
+
+#[test]
+fn number_literals() {
+    let integer = |value| (NumberValue::Integer(value), None);
+    let fraction = |mantissa, exponent| (NumberValue::Fraction { mantissa, exponent }, None);
+
+    assert_eq!(number("1_000"), integer(1000));
+    assert_eq!(number("0x1F"), integer(31));
+    assert_eq!(number("0b1010_1010"), integer(170));
+    assert_eq!(number("0o17"), integer(15));
+
+    assert_eq!(number("6.022e23"), integer(6022 * 10u128.pow(20)));
+    assert_eq!(number("1E+2"), integer(100));
+    assert_eq!(number("2.50"), fraction(25, -1));
+    assert_eq!(number("15e-4"), fraction(15, -4));
+
+    assert_eq!(
+        number("5nat"),
+        (NumberValue::Integer(5), Some(NumberSuffix::Nat))
+    );
+    assert_eq!(
+        number("0xFFint"),
+        (NumberValue::Integer(255), Some(NumberSuffix::Int))
+    );
+    assert_eq!(
+        number("1e3int"),
+        (NumberValue::Integer(1000), Some(NumberSuffix::Int))
+    );
+}
+
+#[test]
+fn lex_number_literals() {
+    let db = Database::default();
+    let text = "0x1.8 0x1E+1 1.5e-3nat 0b1_0";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let tokens: Vec<_> = lex(&db, source)
+        .iter()
+        .filter(|(token, _)| !token.is_trivia())
+        .map(|(token, _)| token.clone())
+        .collect();
+
+    // Prefixed literals take in a fraction, which is reported by the parser,
+    // but never a signed exponent
+    assert_eq!(
+        tokens,
+        [
+            Token::Number("0x1.8".into()),
+            Token::Number("0x1E".into()),
+            Token::Plus,
+            Token::Number("1".into()),
+            Token::Number("1.5e-3nat".into()),
+            Token::Number("0b1_0".into()),
+        ]
+    );
+}
+
+#[test]
+fn hex_digits_are_not_exponents() {
+    assert_eq!(grouped("0x1E+1"), "(1 + 1)");
+    assert_eq!(number("0x1E"), (NumberValue::Integer(30), None));
+}
+
+#[test]
+fn declaration_attributes() {
+    let text = "\
+@inline
+function f() Int
+@runtime
+var x Int
+@runtime
+function g() Int
+    return 1
+end
+";
+
+    assert_eq!(
+        messages(text),
+        "EP15 0..7: unknown attribute '@inline'\n\
+         EP16 25..33: '@runtime' on a declaration with a definition\n\
+         EP16 44..52: '@runtime' on a declaration with a definition\n"
+    );
+}
+
+#[test]
+fn malformed_number_literals() {
+    let text = "\
+function f() Int
+    0x
+    0b12
+    5abc
+    1e
+    0x1.8
+    340282366920938463463374607431768211456
+end
+";
+
+    assert_eq!(
+        messages(text),
+        "EP50 21..23: number literal has no digits\n\
+         EP51 28..32: invalid digit in number literal\n\
+         EP52 37..41: unknown number literal suffix\n\
+         EP52 46..48: unknown number literal suffix\n\
+         EP54 53..58: fractional hexadecimal literals are not supported\n\
+         EP53 63..102: number literal is too large to represent\n"
+    );
+}
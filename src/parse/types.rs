@@ -1,5 +1,6 @@
 use super::Parser;
 use crate::ast::{Type, TypeNode};
+use crate::cst::NodeKind;
 use crate::names::{NameNode, NamePart};
 use crate::token::Token;
 
@@ -23,6 +24,8 @@ impl Parser<'_> {
     /// prefix-type =/ long.type
     /// ```
     fn prefix_type(&mut self) -> Type {
+        let checkpoint = self.checkpoint();
+
         if let Some(opener) = self.consume(Token::Ampersand) {
             let ty = Box::new(self.prefix_type());
             let span = opener + ty.span;
@@ -30,13 +33,13 @@ impl Parser<'_> {
             Type {
                 node: TypeNode::Reference(ty),
                 span,
+                syntax: self.wrap(checkpoint, NodeKind::Type),
             }
         } else if let Some(opener) = self.consume(Token::OpenParen) {
             let args = self.type_list();
-            let closer = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                self.at(opener).parse_missing_paren();
-                self.closest_span()
-            });
+            let closer = self
+                .closing_paren(opener)
+                .unwrap_or_else(|| self.closest_span());
 
             let ret = Box::new(self.prefix_type());
             let span = opener + closer + ret.span;
@@ -44,6 +47,7 @@ impl Parser<'_> {
             Type {
                 node: TypeNode::Function(args, ret),
                 span,
+                syntax: self.wrap(checkpoint, NodeKind::Type),
             }
         } else {
             self.long_type()
@@ -56,21 +60,22 @@ impl Parser<'_> {
     /// field-type   = long-type "." NAME
     /// ```
     fn long_type(&mut self) -> Type {
+        let checkpoint = self.checkpoint();
         let mut ty = self.simple_type();
 
         loop {
             if let Some(opener) = self.consume(Token::OpenParen) {
                 let args = self.type_list();
-                let closer = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                    self.at(opener).parse_missing_paren();
-                    self.closest_span()
-                });
+                let closer = self
+                    .closing_paren(opener)
+                    .unwrap_or_else(|| self.closest_span());
 
                 let span = ty.span + closer;
 
                 ty = Type {
                     node: TypeNode::Applied(Box::new(ty), args),
                     span,
+                    syntax: self.wrap(checkpoint, NodeKind::Type),
                 };
             } else if self.consume(Token::Dot).is_some() {
                 let span = ty.span;
@@ -80,15 +85,18 @@ impl Parser<'_> {
                         NameNode::Value(name) => {
                             this.at(span).parse_expected_type_name(Some(name.as_str()))
                         }
-                        NameNode::Invalid => this.at(span).parse_expected_type_name(None),
+                        NameNode::Quoted(_) | NameNode::Invalid => {
+                            this.at(span).parse_expected_type_name(None)
+                        }
                     }
 
                     TypeNode::Field(Box::new(ty), name)
                 });
 
                 let span = span + closer;
+                let syntax = self.wrap(checkpoint, NodeKind::Type);
 
-                ty = Type { node, span };
+                ty = Type { node, span, syntax };
             } else {
                 break;
             }
@@ -101,6 +109,7 @@ impl Parser<'_> {
     /// simple-type = NAME / "(" type ")"
     /// ```
     fn simple_type(&mut self) -> Type {
+        let checkpoint = self.checkpoint();
         let (node, span) = match self.this_one() {
             Some((Token::TypeName(name), span)) => {
                 let _ = self.next();
@@ -112,10 +121,7 @@ impl Parser<'_> {
                 let _ = self.next();
 
                 let ty = self.parse_type();
-                let _closer = self.consume(Token::CloseParen).unwrap_or_else(|| {
-                    self.at(*opener).parse_missing_paren();
-                    self.closest_span()
-                });
+                let _ = self.closing_paren(*opener);
 
                 return ty;
             }
@@ -137,7 +143,8 @@ impl Parser<'_> {
             }
         };
 
-        Type { node, span }
+        let syntax = self.wrap(checkpoint, NodeKind::Type);
+        Type { node, span, syntax }
     }
 
     /// ```abnf
@@ -0,0 +1,225 @@
+//! This module figures out which modules every source imports, and what
+//! becomes visible in it through them.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{all_names_within, resolve_names, NamesWithin};
+use crate::ast::Import;
+use crate::messages::MessageMaker;
+use crate::names::{Name, NameNode, NamePart, NamePrefix};
+use crate::parse::parse;
+use crate::rst::Class;
+use crate::source::{Project, Source};
+use crate::Db;
+
+#[salsa::tracked]
+pub struct Imports {
+    /// The modules imported by the source itself, whose top-level names are
+    /// visible in it.
+    #[return_ref]
+    pub direct: Vec<Source>,
+
+    /// The prelude of the project, unless this is the prelude itself.
+    pub prelude: Option<Source>,
+
+    /// Every module the source depends on, either directly or through the
+    /// imports of other modules.
+    #[return_ref]
+    pub all: Vec<Source>,
+}
+
+#[salsa::tracked]
+pub fn resolve_imports(db: &dyn Db, source: Source) -> Imports {
+    let mut direct = Vec::new();
+
+    for (import, found) in find_modules(db, source) {
+        let module = match found.as_slice() {
+            [module] => *module,
+
+            [] => {
+                let path = import_path(db, import).join(".");
+                MessageMaker::at(db, import.span).resolve_unknown_module(&path);
+                continue;
+            }
+
+            found => {
+                let names = found.iter().map(|module| module.name(db).clone()).collect();
+                MessageMaker::at(db, import.span).resolve_ambiguous_module(names);
+                continue;
+            }
+        };
+
+        if let Some(through) = cycle(db, module, source) {
+            MessageMaker::at(db, import.span).resolve_import_cycle(through);
+        }
+
+        if !direct.contains(&module) {
+            direct.push(module);
+        }
+    }
+
+    let prelude = Project::try_get(db)
+        .map(|project| project.prelude(db))
+        .filter(|prelude| *prelude != source);
+
+    // Cycles have been reported above, but are otherwise harmless here.
+    let mut all = Vec::new();
+    let mut seen = HashSet::from([source]);
+    let mut queue: VecDeque<_> = direct.iter().copied().chain(prelude).collect();
+
+    while let Some(module) = queue.pop_front() {
+        if seen.insert(module) {
+            all.push(module);
+            queue.extend(imported(db, module));
+        }
+    }
+
+    Imports::new(db, direct, prelude, all)
+}
+
+/// Find what `name` refers to at the top level of `source`. Its own items come
+/// first, then those of the modules it imports and finally those of the
+/// prelude. Every candidate from the first of these declaring the name is
+/// returned, so there is more than one only if the name is ambiguous.
+pub fn lookup_top_level(db: &dyn Db, source: Source, name: NamePart) -> Vec<Name> {
+    let declared = |module: Source| {
+        let name = Name::new(db, NamePrefix::Source(module), name);
+        all_names_within(db, module)
+            .names(db)
+            .contains_key(&name)
+            .then_some(name)
+    };
+
+    if let Some(name) = declared(source) {
+        return vec![name];
+    }
+
+    let imports = resolve_imports(db, source);
+    let imported: Vec<_> = imports
+        .direct(db)
+        .iter()
+        .filter_map(|module| declared(*module))
+        .collect();
+
+    if !imported.is_empty() {
+        return imported;
+    }
+
+    imports.prelude(db).and_then(declared).into_iter().collect()
+}
+
+/// The classes declared in a source and in every module it depends on.
+#[salsa::tracked(return_ref)]
+pub fn visible_classes(db: &dyn Db, source: Source) -> HashMap<Name, Class> {
+    let mut classes = resolve_names(db, source).tree(db).classes(db).clone();
+
+    for module in resolve_imports(db, source).all(db) {
+        let declared = resolve_names(db, *module).tree(db).classes(db);
+        classes.extend(declared.iter().map(|(name, class)| (*name, class.clone())));
+    }
+
+    classes
+}
+
+/// Like [`all_names_within`], but including the names declared in every module
+/// the source depends on.
+#[salsa::tracked]
+pub fn visible_names(db: &dyn Db, source: Source) -> NamesWithin {
+    let mut names = HashMap::new();
+    let mut spans = HashMap::new();
+    let mut public = HashSet::new();
+
+    let modules = resolve_imports(db, source).all(db);
+    for module in std::iter::once(&source).chain(modules) {
+        let within = all_names_within(db, *module);
+        names.extend(within.names(db).clone());
+        spans.extend(within.spans(db).clone());
+        public.extend(within.public(db).iter().copied());
+    }
+
+    NamesWithin::new(db, names, spans, public)
+}
+
+/// Every import of `source`, along with the sources of the project it could
+/// refer to.
+fn find_modules(db: &dyn Db, source: Source) -> Vec<(&Import, Vec<Source>)> {
+    let sources = Project::try_get(db)
+        .map(|project| project.sources(db).as_slice())
+        .unwrap_or_default();
+
+    parse(db, source)
+        .imports(db)
+        .iter()
+        .map(|import| {
+            let path = import_path(db, import);
+            let found = sources
+                .iter()
+                .filter(|module| module.module(db) == path)
+                .copied()
+                .collect();
+
+            (import, found)
+        })
+        .collect()
+}
+
+/// The modules `source` imports, leaving out imports of unknown or ambiguous
+/// modules.
+fn imported(db: &dyn Db, source: Source) -> Vec<Source> {
+    find_modules(db, source)
+        .into_iter()
+        .filter_map(|(_, found)| match found.as_slice() {
+            [module] => Some(*module),
+            _ => None,
+        })
+        .collect()
+}
+
+/// If importing `module` into `source` leads back to `source`, find the
+/// modules the cycle goes through.
+fn cycle(db: &dyn Db, module: Source, source: Source) -> Option<Vec<String>> {
+    if module == source {
+        return Some(Vec::new());
+    }
+
+    let mut parents = HashMap::from([(module, None)]);
+    let mut queue = VecDeque::from([module]);
+
+    while let Some(current) = queue.pop_front() {
+        for next in imported(db, current) {
+            if next == source {
+                let mut path = vec![current];
+                while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+                    path.push(*parent);
+                }
+
+                return Some(
+                    path.into_iter()
+                        .rev()
+                        .map(|module| module.module(db).join("."))
+                        .collect(),
+                );
+            }
+
+            if let Entry::Vacant(entry) = parents.entry(next) {
+                entry.insert(Some(current));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn import_path(db: &dyn Db, import: &Import) -> Vec<String> {
+    import
+        .module
+        .iter()
+        .map(|part| match part.node(db) {
+            NameNode::Value(name) | NameNode::Type(name) => name.clone(),
+            NameNode::Quoted(name) => format!("\"{name}\""),
+            NameNode::Invalid => "<error>".to_string(),
+        })
+        .collect()
+}
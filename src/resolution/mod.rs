@@ -1,6 +1,8 @@
+mod imports;
 mod resolve;
 mod within;
 
+pub use imports::{lookup_top_level, resolve_imports, visible_classes, visible_names, Imports};
 pub use resolve::{resolve_names, NameInfo};
 pub use within::{all_names_within, NamesWithin};
 
@@ -10,6 +12,9 @@ use crate::names::{Name, NameNode, NamePart, NamePrefix};
 use crate::source::{Source, Span};
 use crate::Db;
 
+#[cfg(test)]
+mod tests;
+
 /// Helper struct for traversing scopes properly.
 struct Contextual<'a, Data> {
     db: &'a dyn Db,
@@ -56,7 +61,9 @@ impl<'a, Data> Contextual<'a, Data> {
     fn declaration_name(&mut self, name: &DeclarationName) -> Name {
         match name.node {
             DeclarationNameNode::Identifier(name) => self.declare_name(name),
-            DeclarationNameNode::Quoted(_) => todo!(),
+            DeclarationNameNode::Quoted(ref name) => {
+                self.declare_name(NamePart::new(self.db, NameNode::Quoted(name.clone())))
+            }
             DeclarationNameNode::Invalid => {
                 self.declare_name(NamePart::new(self.db, NameNode::Invalid))
             }
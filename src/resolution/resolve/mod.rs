@@ -2,13 +2,13 @@ mod traverse;
 
 use std::collections::{HashMap, HashSet};
 
-use super::all_names_within;
+use super::{all_names_within, lookup_top_level};
 use super::{Contextual, NamesWithin};
 use crate::ast;
 use crate::names::{Name, NameNode, NamePart, NamePrefix};
 use crate::parse::parse;
 use crate::rst;
-use crate::source::{Source, Span};
+use crate::source::{Lints, Source, Span};
 use crate::Db;
 
 #[salsa::tracked]
@@ -35,6 +35,22 @@ pub fn resolve_names(db: &dyn Db, source: Source) -> NameInfo {
     NameInfo::new(db, resolver.data.mutable, tree)
 }
 
+/// The locals declared directly within a single local scope. Every local scope
+/// gets a unique index, such that locals with the same name in sibling scopes
+/// are still distinct.
+struct Locals {
+    scope: usize,
+    names: Vec<Name>,
+}
+
+/// A lambda being resolved, along with the locals of enclosing functions it
+/// refers to.
+struct Capturing {
+    /// The number of local scopes outside of the lambda.
+    depth: usize,
+    captures: Vec<Name>,
+}
+
 enum ClassOrValue {
     Class(Name),
     Value(rst::Value),
@@ -43,7 +59,21 @@ enum ClassOrValue {
 struct Resolver {
     names: NamesWithin,
     mutable: HashSet<Name>,
-    locals: Vec<Vec<Name>>,
+    locals: Vec<Locals>,
+    scopes: usize,
+    lints: Lints,
+
+    /// Where every local was declared.
+    declared: HashMap<Name, Span>,
+
+    /// The locals which have been referred to.
+    used: HashSet<Name>,
+
+    /// How many loops the current statement is nested within.
+    loops: usize,
+
+    /// The lambdas the current expression is nested within.
+    lambdas: Vec<Capturing>,
 
     classes: HashMap<Name, rst::Class>,
 }
@@ -54,6 +84,12 @@ impl Resolver {
             names,
             mutable: HashSet::new(),
             locals: Vec::new(),
+            scopes: 0,
+            lints: source.lints(db),
+            declared: HashMap::new(),
+            used: HashSet::new(),
+            loops: 0,
+            lambdas: Vec::new(),
 
             classes: HashMap::new(),
         };
@@ -67,6 +103,7 @@ impl Resolver {
 
         match item.node(this.db) {
             ast::DeclarationNode::Class {
+                type_params,
                 inherits,
                 private,
                 public,
@@ -76,6 +113,7 @@ impl Resolver {
                     name,
                     span,
                     rst::ClassKind::Class,
+                    type_params,
                     inherits,
                     private.iter().chain(public),
                 );
@@ -84,6 +122,7 @@ impl Resolver {
             }
 
             ast::DeclarationNode::Variant {
+                type_params,
                 inherits,
                 private,
                 public,
@@ -93,6 +132,7 @@ impl Resolver {
                     name,
                     span,
                     rst::ClassKind::Variant,
+                    type_params,
                     inherits,
                     private.iter().chain(public),
                 );
@@ -106,13 +146,25 @@ impl Resolver {
             }
 
             ast::DeclarationNode::Function {
+                type_params,
                 this: this_arg,
                 args,
                 return_type,
                 body,
+                runtime,
             } => {
-                let value =
-                    Self::resolve_function(this, name, span, this_arg, args, return_type, body);
+                let value = Self::resolve_function(
+                    this,
+                    name,
+                    span,
+                    type_params,
+                    this_arg,
+                    args,
+                    return_type,
+                    body,
+                    *runtime,
+                );
+
                 ClassOrValue::Value(value)
             }
         }
@@ -127,9 +179,11 @@ impl Resolver {
                 return rst::DeclarationName::Invalid;
             };
 
-            let name = match name.node {
-                ast::DeclarationNameNode::Identifier(ident) => ident,
-                ast::DeclarationNameNode::Quoted(_) => todo!(),
+            let name = match &name.node {
+                ast::DeclarationNameNode::Identifier(ident) => *ident,
+                ast::DeclarationNameNode::Quoted(name) => {
+                    NamePart::new(this.db, NameNode::Quoted(name.clone()))
+                }
                 ast::DeclarationNameNode::Invalid => NamePart::new(this.db, NameNode::Invalid),
             };
 
@@ -140,60 +194,141 @@ impl Resolver {
     }
 
     /// Declare a local variable.
-    fn declare(this: &mut Contextual<Self>, name: &NamePart) -> Name {
-        let scope = this.prefix();
-        let scope = NamePrefix::Local(Box::new(scope), this.data.locals.len());
-
-        let name = Name::new(this.db, scope, *name);
+    fn declare(this: &mut Contextual<Self>, name: &NamePart, span: Span) -> Name {
         let locals = this
             .data
             .locals
-            .last_mut()
+            .last()
             .expect("cannot declare a local in a non-local");
 
-        if locals.contains(&name) {
-            // todo: error!!!
-        } else {
-            locals.push(name);
+        let scope = this.prefix();
+        let scope = NamePrefix::Local(Box::new(scope), locals.scope);
+
+        let name = Name::new(this.db, scope, *name);
+
+        if locals.names.contains(&name) {
+            let other = this.data.declared[&name];
+            this.at(span).resolve_duplicate_local(other);
+            return name;
+        }
+
+        if this.data.lints.shadowing {
+            if let Some(other) = Self::shadowed(this, name.name(this.db)) {
+                this.at(span).resolve_shadowed(other);
+            }
         }
 
+        this.data
+            .locals
+            .last_mut()
+            .expect("cannot declare a local in a non-local")
+            .names
+            .push(name);
+        this.data.declared.insert(name, span);
+
         name
     }
 
+    /// Find where the local or member a new local called `name` would shadow is
+    /// declared, if there is any.
+    fn shadowed(this: &Contextual<Self>, name: NamePart) -> Option<Span> {
+        let (_, outer) = this.data.locals.split_last()?;
+        for scope in outer.iter().rev() {
+            if let Some(var) = scope.names.iter().find(|var| var.name(this.db) == name) {
+                return this.data.declared.get(var).copied();
+            }
+        }
+
+        let member = Self::lookup_member(this, name)?;
+        this.data.names.spans(this.db).get(&member).copied()
+    }
+
     fn resolve(this: &mut Contextual<Self>, span: Span, name: NamePart) -> Option<Name> {
+        let found = Self::lookup(this, span, name);
+        if found.is_none() {
+            this.at(span).resolve_unresolved_name();
+        }
+
+        found
+    }
+
+    /// Like [`Resolver::resolve`], but without reporting an error if the name
+    /// is not found. Names imported from more than one module are still
+    /// reported as ambiguous.
+    fn lookup(this: &mut Contextual<Self>, span: Span, name: NamePart) -> Option<Name> {
         // Look for locals...
-        for scope in this.data.locals.iter().rev() {
-            for var in scope.iter().rev() {
-                if var.name(this.db) == name {
-                    return Some(*var);
-                }
+        for (depth, scope) in this.data.locals.iter().enumerate().rev() {
+            if let Some(var) = scope
+                .names
+                .iter()
+                .rev()
+                .find(|var| var.name(this.db) == name)
+            {
+                let var = *var;
+                Self::capture(this, depth, var);
+                this.data.used.insert(var);
+                return Some(var);
             }
         }
 
         // Then search up through the scopes in the current file...
+        if let Some(member) = Self::lookup_member(this, name) {
+            return Some(member);
+        }
+
+        // Then look if this is a top-level name, either of this source or of
+        // the modules it imports...
+        let found = lookup_top_level(this.db, this.within.0, name);
+        if found.len() > 1 {
+            let declared = found
+                .iter()
+                .filter_map(|found| {
+                    let NamePrefix::Source(module) = found.scope(this.db) else {
+                        return None;
+                    };
+
+                    let span = all_names_within(this.db, module).spans(this.db)[found];
+                    Some((module.module(this.db).join("."), span))
+                })
+                .collect();
+
+            this.at(span).resolve_ambiguous_name(name, declared);
+        }
+
+        found.first().copied()
+    }
+
+    /// Look for a name declared within one of the items the current scope is
+    /// nested in.
+    fn lookup_member(this: &Contextual<Self>, name: NamePart) -> Option<Name> {
         let names = this.data.names.names(this.db);
 
-        let (source, mut scopes) = this.within.clone();
-        while let Some(scope) = scopes.pop() {
-            let Some(names) = names.get(&scope) else { continue; };
-            let name = Name::new(this.db, NamePrefix::Item(scope), name);
+        for scope in this.within.1.iter().rev() {
+            let Some(names) = names.get(scope) else { continue; };
+            let name = Name::new(this.db, NamePrefix::Item(*scope), name);
 
             if names.contains(&name) {
                 return Some(name);
             }
         }
 
-        // Then look if this is a top-level name...
-        let name = Name::new(this.db, NamePrefix::Source(source), name);
-        if names.contains_key(&name) {
-            return Some(name);
-        }
-
-        // No name!
-        this.at(span).resolve_unresolved_name();
         None
     }
 
+    /// Record a local found in the local scope at `depth` as captured by every
+    /// lambda between that scope and the current one.
+    fn capture(this: &mut Contextual<Self>, depth: usize, name: Name) {
+        for lambda in this.data.lambdas.iter_mut().rev() {
+            if lambda.depth <= depth {
+                break;
+            }
+
+            if !lambda.captures.contains(&name) {
+                lambda.captures.push(name);
+            }
+        }
+    }
+
     fn item_scope<T, F>(this: &mut Contextual<Self>, name: rst::DeclarationName, f: F) -> T
     where
         F: FnOnce(&mut Contextual<Self>) -> T,
@@ -207,28 +342,86 @@ impl Resolver {
         F: FnOnce(&mut Contextual<Self>) -> T,
     {
         let name = Self::make_scope_name(this, name);
+        this.in_scope(name, |this| Self::block_scope(this, f))
+    }
+
+    /// Open a new scope for locals, such as the body of a function or of a
+    /// `case` arm.
+    fn block_scope<T, F>(this: &mut Contextual<Self>, f: F) -> T
+    where
+        F: FnOnce(&mut Contextual<Self>) -> T,
+    {
+        let scope = this.data.scopes;
+        this.data.scopes += 1;
+
         let before = this.data.locals.len();
-        this.data.locals.push(Vec::new());
+        this.data.locals.push(Locals {
+            scope,
+            names: Vec::new(),
+        });
 
-        let result = this.in_scope(name, f);
+        let result = f(this);
 
-        this.data.locals.pop();
+        let locals = this.data.locals.pop().expect("unbalanced local scopes");
         assert_eq!(before, this.data.locals.len());
 
+        if this.data.lints.unused {
+            for name in locals.names {
+                if !this.data.used.contains(&name) {
+                    this.at(this.data.declared[&name]).resolve_unused_local();
+                }
+            }
+        }
+
         result
     }
 
+    /// Open a new scope for the body of a loop, in which `break` and `continue`
+    /// are allowed.
+    fn loop_scope<T, F>(this: &mut Contextual<Self>, f: F) -> T
+    where
+        F: FnOnce(&mut Contextual<Self>) -> T,
+    {
+        this.data.loops += 1;
+        let result = Self::block_scope(this, f);
+        this.data.loops -= 1;
+
+        result
+    }
+
+    /// Open a new scope for the body of a lambda, which is not within any loop
+    /// of its enclosing function. Returns the result of `f` along with the
+    /// locals of enclosing functions the lambda captures.
+    fn lambda_scope<T, F>(this: &mut Contextual<Self>, f: F) -> (T, Vec<Name>)
+    where
+        F: FnOnce(&mut Contextual<Self>) -> T,
+    {
+        this.data.lambdas.push(Capturing {
+            depth: this.data.locals.len(),
+            captures: Vec::new(),
+        });
+
+        let loops = std::mem::take(&mut this.data.loops);
+        let result = Self::block_scope(this, f);
+        this.data.loops = loops;
+
+        let lambda = this.data.lambdas.pop().expect("unbalanced lambda scopes");
+        (result, lambda.captures)
+    }
+
     /// Create a [`Name`] from the given [`rst::DeclarationName`]. This is used
     /// to unambiguously refer to scopes, even in items whose names are invalid
     /// or fields of other types (e.g. when overriding functions).
     ///
     /// Should produce the same result as `this.declaration_name` on the
     /// corresponding `ast::DeclarationName`.
-    fn make_scope_name(_this: &mut Contextual<Self>, name: rst::DeclarationName) -> Name {
-        match name {
-            rst::DeclarationName::Name(name) => name,
-            rst::DeclarationName::Field(..) => todo!(),
-            rst::DeclarationName::Invalid => todo!(),
+    fn make_scope_name(this: &mut Contextual<Self>, name: rst::DeclarationName) -> Name {
+        match name.declared(this.db, this.prefix()) {
+            Some(name) => name,
+            None => {
+                let invalid = NamePart::new(this.db, NameNode::Invalid);
+                Name::new(this.db, this.prefix(), invalid)
+            }
         }
     }
 }
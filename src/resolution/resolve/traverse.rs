@@ -1,7 +1,7 @@
 use itertools::{Either, Itertools};
 
 use super::{ClassOrValue, Contextual, Resolver};
-use crate::names::{Name, NamePart};
+use crate::names::{Name, NameNode, NamePart};
 use crate::source::Span;
 use crate::{ast, rst};
 
@@ -11,27 +11,33 @@ impl Resolver {
         name: rst::DeclarationName,
         span: Span,
         kind: rst::ClassKind,
+        type_params: &[ast::TypeParameter],
         inherits: &[ast::Type],
         items: impl Iterator<Item = &'a ast::Declaration>,
     ) -> Name {
-        let inherits = inherits
-            .iter()
-            .map(|ty| Self::resolve_type(this, ty))
-            .collect();
+        let (type_params, inherits, (classes, values)) = Self::item_scope(this, name, |this| {
+            let type_params = Self::declare_type_params(this, type_params);
+
+            let inherits = inherits
+                .iter()
+                .map(|ty| Self::resolve_type(this, ty))
+                .collect();
 
-        let (classes, values) = Self::item_scope(this, name, |this| {
-            items
+            let fields = items
                 .map(|item| Self::resolve_item(this, item))
                 .partition_map(|item| match item {
                     ClassOrValue::Class(class) => Either::Left(class),
                     ClassOrValue::Value(value) => Either::Right(value),
-                })
+                });
+
+            (type_params, inherits, fields)
         });
 
         let fields = rst::Fields { classes, values };
         let class = rst::Class {
             name,
             kind,
+            type_params,
             inherits,
             fields,
             span,
@@ -62,47 +68,98 @@ impl Resolver {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn resolve_function(
         this: &mut Contextual<Self>,
         name: rst::DeclarationName,
         span: Span,
+        type_params: &[ast::TypeParameter],
         this_arg: &Option<usize>,
-        args: &[(NamePart, ast::Type)],
+        args: &[(NamePart, Span, ast::Type)],
         return_type: &ast::Type,
         body: &Option<ast::Block>,
+        runtime: bool,
     ) -> rst::Value {
         Self::local_scope(this, name, |this| {
+            let type_params = Self::declare_type_params(this, type_params)
+                .into_iter()
+                .map(|param| param.name)
+                .collect();
+
             let args = args
                 .iter()
-                .map(|(name, ty)| {
-                    let name = Self::declare(this, name);
+                .map(|(name, span, ty)| {
+                    let name = Self::declare(this, name, *span);
                     let ty = Self::resolve_type(this, ty);
                     (name, ty)
                 })
-                .collect();
+                .collect::<Vec<_>>();
 
             let return_type = Self::resolve_type(this, return_type);
 
             let body = body.as_ref().map(|body| Self::resolve_block(this, body));
 
+            // The arguments of abstract functions are only there to document
+            // the signature
+            if body.is_none() {
+                this.data.used.extend(args.iter().map(|(name, _)| *name));
+            }
+
             rst::Value {
                 name,
                 node: rst::ValueNode::Function {
+                    type_params,
                     this: *this_arg,
                     args,
                     return_type,
                     body,
+                    runtime,
                 },
                 span,
             }
         })
     }
 
+    /// Get the names of the type parameters of the item whose scope we're in,
+    /// which were declared by [`all_names_within`](crate::resolution::all_names_within).
+    fn declare_type_params(
+        this: &mut Contextual<Self>,
+        params: &[ast::TypeParameter],
+    ) -> Vec<rst::TypeParameter> {
+        params
+            .iter()
+            .map(|param| rst::TypeParameter {
+                name: this.declare_name(param.name),
+                variance: param.variance,
+                span: param.span,
+            })
+            .collect()
+    }
+
+    /// The builtin type with the given name, which is only used if nothing else
+    /// by that name is in scope.
+    fn builtin_type(this: &mut Contextual<Self>, name: NamePart) -> Option<rst::TypeNode> {
+        let NameNode::Type(name) = name.node(this.db) else {
+            return None;
+        };
+
+        match name.as_str() {
+            "Int" => Some(rst::TypeNode::Int),
+            "Nat" => Some(rst::TypeNode::Nat),
+            "Boolean" => Some(rst::TypeNode::Boolean),
+            "Unit" => Some(rst::TypeNode::Unit),
+            _ => None,
+        }
+    }
+
     fn resolve_type(this: &mut Contextual<Self>, ty: &ast::Type) -> rst::Type {
         let node = match &ty.node {
-            ast::TypeNode::Name(name) => match Self::resolve(this, ty.span, *name) {
+            ast::TypeNode::Name(name) => match Self::lookup(this, ty.span, *name) {
                 Some(name) => rst::TypeNode::Name(name),
-                None => rst::TypeNode::Invalid,
+                None => Self::builtin_type(this, *name).unwrap_or_else(|| {
+                    this.at(ty.span).resolve_unresolved_name();
+                    rst::TypeNode::Invalid
+                }),
             },
 
             ast::TypeNode::Field(of, field) => {
@@ -168,11 +225,11 @@ impl Resolver {
             }
 
             ast::StatementNode::Variable(name, anno, body) => {
-                let name = Self::declare(this, name);
+                let name = Self::declare(this, name, span);
                 let anno = Self::resolve_type(this, anno);
                 let body = Self::resolve_expression(this, body);
 
-                assert!(this.data.mutable.insert(name));
+                this.data.mutable.insert(name);
                 declarations.push((name, anno));
 
                 let target = rst::Expression {
@@ -184,7 +241,7 @@ impl Resolver {
             }
 
             ast::StatementNode::Constant(name, anno, body) => {
-                let name = Self::declare(this, name);
+                let name = Self::declare(this, name, span);
                 let anno = Self::resolve_type(this, anno);
                 let body = Self::resolve_expression(this, body);
 
@@ -211,6 +268,54 @@ impl Resolver {
                 rst::StatementNode::Return(expr)
             }
 
+            ast::StatementNode::Case(scrutinee, arms) => {
+                let scrutinee = Self::resolve_expression(this, scrutinee);
+                let arms = arms
+                    .iter()
+                    .map(|arm| Self::resolve_case_arm(this, arm))
+                    .collect();
+
+                rst::StatementNode::Case(scrutinee, arms)
+            }
+
+            ast::StatementNode::If(cond, then, otherwise) => {
+                let cond = Self::resolve_expression(this, cond);
+                let then = Self::block_scope(this, |this| Self::resolve_block(this, then));
+                let otherwise = otherwise
+                    .as_ref()
+                    .map(|block| Self::block_scope(this, |this| Self::resolve_block(this, block)));
+
+                rst::StatementNode::If(cond, then, otherwise)
+            }
+
+            ast::StatementNode::While(cond, body) => {
+                let cond = Self::resolve_expression(this, cond);
+                let body = Self::loop_scope(this, |this| Self::resolve_block(this, body));
+
+                rst::StatementNode::While(cond, body)
+            }
+
+            ast::StatementNode::Loop(body) => {
+                let body = Self::loop_scope(this, |this| Self::resolve_block(this, body));
+                rst::StatementNode::Loop(body)
+            }
+
+            ast::StatementNode::Break => {
+                if this.data.loops == 0 {
+                    this.at(span).resolve_outside_loop("break");
+                }
+
+                rst::StatementNode::Break
+            }
+
+            ast::StatementNode::Continue => {
+                if this.data.loops == 0 {
+                    this.at(span).resolve_outside_loop("continue");
+                }
+
+                rst::StatementNode::Continue
+            }
+
             ast::StatementNode::Null => rst::StatementNode::Null,
             ast::StatementNode::Invalid => rst::StatementNode::Expression(rst::Expression {
                 node: rst::ExpressionNode::Invalid,
@@ -221,6 +326,75 @@ impl Resolver {
         rst::Statement { node, span }
     }
 
+    fn resolve_case_arm(this: &mut Contextual<Self>, arm: &ast::CaseArm) -> rst::CaseArm {
+        Self::block_scope(this, |this| {
+            let pattern = Self::resolve_pattern(this, &arm.pattern);
+            let body = Self::resolve_block(this, &arm.body);
+
+            rst::CaseArm {
+                pattern,
+                body,
+                span: arm.span,
+            }
+        })
+    }
+
+    fn resolve_pattern(this: &mut Contextual<Self>, pattern: &ast::Pattern) -> rst::Pattern {
+        let node = match &pattern.node {
+            ast::PatternNode::Binding(name) => {
+                let name = Self::declare(this, name, pattern.span);
+                rst::PatternNode::Binding(name)
+            }
+
+            ast::PatternNode::Class(class, fields) => {
+                let class = Self::resolve_type(this, class);
+                let fields = fields
+                    .iter()
+                    .map(|pattern| Self::resolve_pattern(this, pattern))
+                    .collect();
+
+                rst::PatternNode::Class(class, fields)
+            }
+
+            ast::PatternNode::Invalid => rst::PatternNode::Invalid,
+        };
+
+        rst::Pattern {
+            node,
+            span: pattern.span,
+        }
+    }
+
+    fn resolve_lambda(this: &mut Contextual<Self>, lambda: &ast::Lambda) -> rst::Lambda {
+        let ((params, return_type, body), captures) = Self::lambda_scope(this, |this| {
+            let params = lambda
+                .params
+                .iter()
+                .map(|(name, span, ty)| {
+                    let name = Self::declare(this, name, *span);
+                    let ty = ty.as_ref().map(|ty| Self::resolve_type(this, ty));
+                    (name, ty)
+                })
+                .collect();
+
+            let return_type = lambda
+                .return_type
+                .as_ref()
+                .map(|ty| Self::resolve_type(this, ty));
+
+            let body = Self::resolve_block(this, &lambda.body);
+
+            (params, return_type, body)
+        });
+
+        rst::Lambda {
+            params,
+            return_type,
+            body,
+            captures,
+        }
+    }
+
     fn resolve_expression(this: &mut Contextual<Self>, expr: &ast::Expression) -> rst::Expression {
         let node = match &expr.node {
             ast::ExpressionNode::Reference(expr) => {
@@ -228,6 +402,17 @@ impl Resolver {
                 rst::ExpressionNode::Reference(expr)
             }
 
+            ast::ExpressionNode::Binary(op, lhs, rhs) => {
+                let lhs = Box::new(Self::resolve_expression(this, lhs));
+                let rhs = Box::new(Self::resolve_expression(this, rhs));
+                rst::ExpressionNode::Binary(*op, lhs, rhs)
+            }
+
+            ast::ExpressionNode::Unary(op, expr) => {
+                let expr = Box::new(Self::resolve_expression(this, expr));
+                rst::ExpressionNode::Unary(*op, expr)
+            }
+
             ast::ExpressionNode::Call(fun, args) => {
                 let fun = Box::new(Self::resolve_expression(this, fun));
                 let args = args
@@ -249,6 +434,24 @@ impl Resolver {
 
             ast::ExpressionNode::Number(num) => rst::ExpressionNode::Number(num.clone()),
             ast::ExpressionNode::String(num) => rst::ExpressionNode::String(num.clone()),
+            ast::ExpressionNode::Interpolated(parts) => {
+                let parts = parts
+                    .iter()
+                    .map(|part| match part {
+                        ast::StringPart::Text(text) => rst::StringPart::Text(text.clone()),
+                        ast::StringPart::Expression(expr) => {
+                            rst::StringPart::Expression(Self::resolve_expression(this, expr))
+                        }
+                    })
+                    .collect();
+
+                rst::ExpressionNode::Interpolated(parts)
+            }
+
+            ast::ExpressionNode::Lambda(lambda) => {
+                rst::ExpressionNode::Lambda(Self::resolve_lambda(this, lambda))
+            }
+
             ast::ExpressionNode::This => rst::ExpressionNode::This,
             ast::ExpressionNode::Unit => rst::ExpressionNode::Unit,
 
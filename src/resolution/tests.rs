@@ -0,0 +1,288 @@
+use crate::messages::{LabelKind, Message};
+use crate::names::NameNode;
+use crate::rst::{Block, ExpressionNode, Lambda, StatementNode, ValueNode};
+use crate::source::{prelude, Lints, Project, Source};
+use crate::types::annotate;
+use crate::Messages;
+
+use super::resolve_names;
+
+#[derive(Default)]
+#[salsa::db(crate::Jar)]
+struct Database {
+    storage: salsa::Storage<Self>,
+}
+
+impl salsa::Database for Database {}
+
+/// The code of every message, along with the text at each of its labels.
+fn describe(text: &str, message: &Message) -> (String, Vec<String>) {
+    let labels = message
+        .labels
+        .iter()
+        .filter(|label| label.kind != LabelKind::Help)
+        .map(|label| text[label.at.start..label.at.end].to_string())
+        .collect();
+
+    (message.code.clone().unwrap(), labels)
+}
+
+#[test]
+fn local_diagnostics() {
+    let db = Database::default();
+    let text = "\
+class Point
+    function x(this) Int
+        return 0
+    end
+
+    function shift(this &, by Int) Int
+        let x Int := by
+        let x Int := 0
+        return x
+    end
+end
+
+function main(unused Int) Unit
+    let outer Int := 1
+    if outer < 2
+        let outer Int := 3
+        outer
+    end
+end
+";
+
+    let describe_all = |lints| {
+        let source = Source::new(&db, text.into(), String::new(), lints);
+        resolve_names(&db, source);
+        resolve_names::accumulated::<Messages>(&db, source)
+            .iter()
+            .map(|message| describe(text, message))
+            .collect::<Vec<_>>()
+    };
+
+    let owned = |code: &str, labels: &[&str]| {
+        let labels = labels.iter().map(|label| label.to_string()).collect();
+        (code.to_string(), labels)
+    };
+
+    assert_eq!(
+        describe_all(Lints::default()),
+        [
+            owned(
+                "WR00",
+                &[
+                    "let x Int := by",
+                    "function x(this) Int\n        return 0\n    end"
+                ]
+            ),
+            owned("ER04", &["let x Int := 0", "let x Int := by"]),
+            owned("WR00", &["let outer Int := 3", "let outer Int := 1"]),
+            owned("WR01", &["unused"]),
+        ]
+    );
+
+    assert_eq!(
+        describe_all(Lints::NONE),
+        [owned("ER04", &["let x Int := 0", "let x Int := by"])]
+    );
+}
+
+#[test]
+fn import_diagnostics() {
+    let db = Database::default();
+    let source = |name: &str, text: &str| Source::new(&db, text.into(), name.into(), Lints::NONE);
+
+    let main = source(
+        "main.rry",
+        "\
+import shapes
+import util.helpers
+import geometry
+import missing
+import twice
+
+class Square is Shape
+    var side Int
+
+    function Shape.area(this &) Int
+        return this.side * this.side
+    end
+end
+
+function main() Int
+    return total(Square(2)) + helper()
+end
+",
+    );
+
+    let shapes = source(
+        "shapes.rry",
+        "\
+class Shape
+    function area(this &) Int
+end
+
+function total(shape Shape) Int
+    return shape.area()
+end
+",
+    );
+
+    let helpers = source(
+        "util/helpers.rry",
+        "\
+function helper() Int
+    return 1
+end
+",
+    );
+
+    let geometry = source(
+        "geometry.rry",
+        "\
+import main
+
+function helper() Int
+    return 2
+end
+",
+    );
+
+    let twice = source("twice.rry", "");
+    let again = source("twice.brew", "");
+    let sources = vec![main, shapes, helpers, geometry, twice, again];
+    Project::new(&db, sources, prelude(&db));
+
+    // Messages about imported modules are reported along with them
+    annotate(&db, main);
+    let messages: Vec<_> = annotate::accumulated::<Messages>(&db, main)
+        .into_iter()
+        .filter(|message| message.labels[0].at.source == main)
+        .collect();
+
+    let mut codes: Vec<_> = messages
+        .iter()
+        .filter_map(|message| message.code.as_deref())
+        .collect();
+    codes.sort();
+    assert_eq!(codes, ["ER06", "ER07", "ER08", "ER09"]);
+
+    let cycle = messages
+        .iter()
+        .find(|message| message.code.as_deref() == Some("ER08"))
+        .unwrap();
+    assert_eq!(
+        cycle.labels[0].message.as_deref(),
+        Some("this module ends up importing itself through 'geometry'")
+    );
+}
+
+#[test]
+fn case_pattern_bindings() {
+    let db = Database::default();
+    let text = "\
+variant Shape
+    class Circle
+        var radius Int
+    end
+
+    class Rectangle
+        var width Int
+        var height Int
+    end
+end
+
+function area(shape Shape) Int
+    case shape
+        is Shape.Circle(radius)
+            return radius * radius
+        end
+
+        is Shape.Rectangle(width, height)
+            return width * height * radius
+        end
+    end
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    resolve_names(&db, source);
+    let messages: Vec<_> = resolve_names::accumulated::<Messages>(&db, source)
+        .iter()
+        .map(|message| describe(text, message))
+        .collect();
+
+    // Every binding is a local of its own arm only
+    assert_eq!(messages, [("ER01".to_string(), vec!["radius".to_string()])]);
+}
+
+/// Every lambda assigned or returned in a block, including those nested in
+/// other lambdas, in source order.
+fn lambdas(block: &Block) -> Vec<&Lambda> {
+    let mut found = Vec::new();
+
+    for statement in block.statements.iter() {
+        let (StatementNode::Assignment(_, expr) | StatementNode::Return(expr)) = &statement.node
+        else {
+            continue;
+        };
+
+        if let ExpressionNode::Lambda(lambda) = &expr.node {
+            found.push(lambda);
+            found.extend(lambdas(&lambda.body));
+        }
+    }
+
+    found
+}
+
+#[test]
+fn lambda_captures() {
+    let db = Database::default();
+    let text = "\
+function main(x Int) Unit
+    let outer () Int := function() Int
+        return x
+    end
+
+    let nested (Int) () Int := function(y Int) () Int
+        return function() Int
+            return x + y
+        end
+    end
+
+    let own (Int) Int := function(y Int) Int
+        let z Int := y
+        return z
+    end
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let values = resolve_names(&db, source).tree(&db).values(&db);
+    let ValueNode::Function {
+        body: Some(body), ..
+    } = &values[0].node
+    else {
+        panic!("expected main to have a body");
+    };
+
+    let captures: Vec<Vec<_>> = lambdas(body)
+        .iter()
+        .map(|lambda| {
+            lambda
+                .captures
+                .iter()
+                .map(|name| match name.name(&db).node(&db) {
+                    NameNode::Value(name) => name.as_str(),
+                    _ => panic!("captured locals have value names"),
+                })
+                .collect()
+        })
+        .collect();
+
+    // Lambdas capture the locals of enclosing functions and lambdas, but not
+    // their own parameters or locals
+    assert_eq!(captures, [vec!["x"], vec!["x"], vec!["x", "y"], vec![]]);
+}
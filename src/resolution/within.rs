@@ -2,8 +2,10 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::ast::{Declaration, DeclarationNode};
-use crate::names::Name;
+use crate::ast::{
+    is_operator_name, Declaration, DeclarationNameNode, DeclarationNode, TypeParameter,
+};
+use crate::names::{Name, NamePrefix};
 use crate::parse::parse;
 use crate::source::{Source, Span};
 use crate::Db;
@@ -22,6 +24,32 @@ pub struct NamesWithin {
     pub public: HashSet<Name>,
 }
 
+impl NamesWithin {
+    /// Can `name` be referred to from within the item `from`? Private members
+    /// are only visible within the class declaring them and the items nested
+    /// in it. In particular, subclasses cannot see the private members they
+    /// inherit.
+    pub fn is_visible(&self, db: &dyn Db, name: Name, from: Name) -> bool {
+        let NamePrefix::Item(owner) = name.scope(db) else {
+            return true;
+        };
+
+        if self.public(db).contains(&name) {
+            return true;
+        }
+
+        let mut scope = NamePrefix::Item(from);
+        loop {
+            scope = match scope {
+                NamePrefix::Item(item) if item == owner => return true,
+                NamePrefix::Item(item) => item.scope(db),
+                NamePrefix::Local(scope, _) => *scope,
+                NamePrefix::Type(_) | NamePrefix::Source(_) => return false,
+            };
+        }
+    }
+}
+
 #[salsa::tracked]
 pub fn all_names_within(db: &dyn Db, source: Source) -> NamesWithin {
     let declarations = parse(db, source);
@@ -60,6 +88,13 @@ impl Declarer {
     fn declare(this: &mut Contextual<Self>, item: Declaration) -> Name {
         let name = item.name(this.db);
         let span = item.span(this.db);
+
+        if let DeclarationNameNode::Quoted(quoted) = &name.node {
+            if !is_operator_name(quoted) {
+                this.at(name.span).resolve_unknown_operator(quoted);
+            }
+        }
+
         let name = this.declaration_name(name);
 
         if let Some((other, _)) = this.data.names.get(&name) {
@@ -70,12 +105,20 @@ impl Declarer {
 
         match item.node(this.db) {
             DeclarationNode::Class {
-                public, private, ..
+                type_params,
+                public,
+                private,
+                ..
             }
             | DeclarationNode::Variant {
-                public, private, ..
+                type_params,
+                public,
+                private,
+                ..
             } => {
                 this.in_scope(name, |this| {
+                    Self::declare_type_params(this, name, type_params);
+
                     for item in public {
                         let child = Self::declare(this, *item);
                         this.data.public.insert(child);
@@ -89,12 +132,32 @@ impl Declarer {
                 });
             }
 
-            DeclarationNode::Variable { .. } | DeclarationNode::Function { .. } => {}
+            DeclarationNode::Function { type_params, .. } => {
+                this.in_scope(name, |this| {
+                    Self::declare_type_params(this, name, type_params);
+                });
+            }
+
+            DeclarationNode::Variable { .. } => {}
         }
 
         name
     }
 
+    /// Declare the type parameters of a generic item within its scope.
+    fn declare_type_params(this: &mut Contextual<Self>, parent: Name, params: &[TypeParameter]) {
+        for param in params {
+            let child = this.declare_name(param.name);
+
+            if let Some((other, _)) = this.data.names.get(&child) {
+                this.at(param.span).resolve_duplicate_definitions(*other);
+            }
+
+            this.data.names.insert(child, (param.span, HashSet::new()));
+            Self::add_child(this, parent, child);
+        }
+    }
+
     fn add_child(this: &mut Contextual<Self>, parent: Name, child: Name) {
         this.data
             .names
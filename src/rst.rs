@@ -2,8 +2,10 @@
 
 use std::collections::HashMap;
 
-use crate::names::{Name, NamePart};
+use crate::ast::{BinaryOperator, NumberLiteral, UnaryOperator, Variance};
+use crate::names::{Name, NamePart, NamePrefix};
 use crate::source::Span;
+use crate::Db;
 
 #[salsa::tracked]
 pub struct Items {
@@ -24,11 +26,19 @@ pub struct Fields {
 pub struct Class {
     pub name: DeclarationName,
     pub kind: ClassKind,
+    pub type_params: Vec<TypeParameter>,
     pub fields: Fields,
     pub inherits: Vec<Type>,
     pub span: Span,
 }
 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TypeParameter {
+    pub name: Name,
+    pub variance: Variance,
+    pub span: Span,
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Value {
     pub name: DeclarationName,
@@ -39,10 +49,15 @@ pub struct Value {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ValueNode {
     Function {
+        type_params: Vec<Name>,
         this: Option<usize>,
         args: Vec<(Name, Type)>,
         return_type: Type,
         body: Option<Block>,
+
+        /// Is the function provided by the runtime? If not, a function without
+        /// a body is abstract.
+        runtime: bool,
     },
 
     Variable {
@@ -64,6 +79,18 @@ pub enum DeclarationName {
     Invalid,
 }
 
+impl DeclarationName {
+    /// The name this declares when declared in `scope`. Overrides like
+    /// `Parent.member` are named as if they had no `Parent.` prefix.
+    pub fn declared(&self, db: &dyn Db, scope: NamePrefix) -> Option<Name> {
+        match self {
+            Self::Name(name) => Some(*name),
+            Self::Field(_, part) => Some(Name::new(db, scope, *part)),
+            Self::Invalid => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Type {
     pub node: TypeNode,
@@ -106,10 +133,40 @@ pub enum StatementNode {
     Expression(Expression),
     Assignment(Expression, Expression),
     Return(Expression),
+    Case(Expression, Vec<CaseArm>),
+
+    If(Expression, Block, Option<Block>),
+    While(Expression, Block),
+    Loop(Block),
+
+    Break,
+    Continue,
 
     Null,
 }
 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CaseArm {
+    pub pattern: Pattern,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Pattern {
+    pub node: PatternNode,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PatternNode {
+    /// A new local, scoped to the body of the arm.
+    Binding(Name),
+    Class(Type, Vec<Pattern>),
+
+    Invalid,
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Expression {
     pub node: ExpressionNode,
@@ -120,14 +177,36 @@ pub struct Expression {
 pub enum ExpressionNode {
     Reference(Box<Expression>),
 
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+
     Call(Box<Expression>, Vec<Expression>),
     Field(Box<Expression>, NamePart),
 
     Name(Name),
-    Number(String),
+    Number(NumberLiteral),
     String(String),
     This,
     Unit,
 
+    Interpolated(Vec<StringPart>),
+    Lambda(Lambda),
+
     Invalid,
 }
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Lambda {
+    pub params: Vec<(Name, Option<Type>)>,
+    pub return_type: Option<Type>,
+    pub body: Block,
+
+    /// The locals of enclosing functions this lambda refers to.
+    pub captures: Vec<Name>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Expression(Expression),
+}
@@ -1,4 +1,7 @@
 use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+use crate::Db;
 
 #[salsa::input]
 pub struct Source {
@@ -7,6 +10,66 @@ pub struct Source {
 
     #[return_ref]
     pub name: String,
+
+    pub lints: Lints,
+}
+
+impl Source {
+    /// The path other sources import this one by: its name without the
+    /// extension, with one part per directory.
+    pub fn module(&self, db: &dyn Db) -> Vec<String> {
+        Path::new(self.name(db))
+            .with_extension("")
+            .iter()
+            .map(|part| part.to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+/// Every source of the program being compiled. Sources can only import
+/// modules which are part of the project.
+#[salsa::input(singleton)]
+pub struct Project {
+    #[return_ref]
+    pub sources: Vec<Source>,
+
+    /// The source every other source implicitly imports, usually the one
+    /// created by [`prelude`].
+    pub prelude: Source,
+}
+
+/// Create a source for the prelude embedded in the crate, which defines core
+/// classes like `String`.
+pub fn prelude(db: &dyn Db) -> Source {
+    let text = include_str!("prelude.rry");
+    Source::new(db, text.into(), "prelude.rry".into(), Lints::NONE)
+}
+
+/// Which optional warnings to report for a source.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Lints {
+    /// Warn about locals which shadow an outer local or a member.
+    pub shadowing: bool,
+
+    /// Warn about locals and arguments which are never used.
+    pub unused: bool,
+}
+
+impl Lints {
+    /// Report none of the optional warnings.
+    pub const NONE: Self = Self {
+        shadowing: false,
+        unused: false,
+    };
+}
+
+impl Default for Lints {
+    fn default() -> Self {
+        Self {
+            shadowing: true,
+            unused: true,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -1,31 +1,64 @@
-use logos::{Lexer, Logos};
+use logos::{FilterResult, Lexer, Logos};
 
+use crate::messages::MessageMaker;
 use crate::source::{Source, Span};
 use crate::Db;
 
 #[salsa::tracked(return_ref)]
 pub fn lex(db: &dyn Db, source: Source) -> Vec<(Token, Span)> {
     let text = source.text(db);
-    Lexer::new(text.as_ref())
-        .spanned()
-        .map(|(token, range)| (token, Span::new(source, range.start, range.end)))
-        .collect()
+    let mut lexer = Token::lexer(text);
+    let mut tokens = Vec::new();
+
+    while let Some(token) = lexer.next() {
+        let range = lexer.span();
+        tokens.push((token, Span::new(source, range.start, range.end)));
+    }
+
+    for (error, range) in lexer.extras {
+        let span = Span::new(source, range.start, range.end);
+        match error {
+            LexError::UnterminatedComment => MessageMaker::at(db, span).lex_unterminated_comment(),
+        }
+    }
+
+    tokens
 }
 
-#[derive(Logos, Clone, Debug, Eq, PartialEq)]
+/// Errors found while lexing, which are reported once the entire source has
+/// been lexed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LexError {
+    UnterminatedComment,
+}
+
+#[derive(Logos, Clone, Debug, Eq, Hash, PartialEq)]
+#[logos(extras = Vec<(LexError, logos::Span)>)]
 pub enum Token {
+    #[token("break")]
+    Break,
     #[token("case")]
     Case,
     #[token("class")]
     Class,
+    #[token("continue")]
+    Continue,
+    #[token("else")]
+    Else,
     #[token("end")]
     End,
     #[token("function")]
     Function,
+    #[token("if")]
+    If,
+    #[token("import")]
+    Import,
     #[token("is")]
     Is,
     #[token("let")]
     Let,
+    #[token("loop")]
+    Loop,
     #[token("null")]
     Null,
     #[token("private")]
@@ -38,6 +71,8 @@ pub enum Token {
     Var,
     #[token("variant")]
     Variant,
+    #[token("while")]
+    While,
 
     #[token("(")]
     OpenParen,
@@ -74,21 +109,55 @@ pub enum Token {
     #[token("/")]
     Slash,
 
-    #[regex(r"\d[0-9_]*(\.\d[0-9_])?", |lex| lex.slice().to_string())]
+    /// A number literal, possibly malformed. Its value and suffix are parsed by
+    /// the parser, which can report errors about it. Only decimal literals may
+    /// have a signed exponent, so `0x1E+1` is an addition.
+    #[regex(
+        r"[0-9][0-9_]*(\.[0-9][0-9_]*)?([eE][+-]?[0-9])?[0-9a-zA-Z_]*",
+        |lex| lex.slice().to_string()
+    )]
+    #[regex(
+        r"0[xbo][0-9a-zA-Z_]*(\.[0-9][0-9a-zA-Z_]*)?",
+        |lex| lex.slice().to_string()
+    )]
     Number(String),
-    #[regex("\"[^\"]+\"", string_content)]
+    #[regex(r#""([^"\\\n]|\\.)*""#, string_content)]
     String(String),
     #[regex(r"[A-Z][a-zA-Z_'?!]*", |lex| lex.slice().to_string())]
     TypeName(String),
     #[regex(r"[a-z][a-zA-Z_'?!]*", |lex| lex.slice().to_string())]
     ValueName(String),
 
-    #[regex(r"[ \r\n\t]+", logos::skip)]
+    /// An attribute like `@runtime`, which applies to the declaration following
+    /// it. The `@` isn't part of its name.
+    #[regex(r"@[a-z][a-zA-Z_]*", |lex| lex.slice()[1..].to_string())]
+    Attribute(String),
+
+    /// A `---` comment, which documents the declaration following it.
+    #[regex(r"---([^-\n][^\n]*)?", doc_comment)]
+    DocComment(String),
+
+    #[regex(r"[ \r\n\t]+")]
+    Whitespace,
+
+    /// A `--` or `#` line comment, or a `{- -}` block comment.
+    #[regex(r"--([^-\n][^\n]*)?")]
+    #[regex(r"----[^\n]*")]
+    #[regex(r"#[^\n]*")]
+    #[token("{-", block_comment)]
+    Comment,
+
     #[error]
     Invalid,
 }
 
 impl Token {
+    /// Whether this token is whitespace or a comment, which the parser ignores
+    /// but the syntax tree keeps.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment)
+    }
+
     pub fn type_name(&self) -> Option<&String> {
         match self {
             Self::TypeName(name) => Some(name),
@@ -104,6 +173,46 @@ impl Token {
     }
 }
 
+fn doc_comment(lexer: &Lexer<Token>) -> String {
+    let content = &lexer.slice()[3..];
+    content.strip_prefix(' ').unwrap_or(content).to_string()
+}
+
+/// Lex a block comment `{- ... -}`, which may contain other nested block
+/// comments.
+fn block_comment(lexer: &mut Lexer<Token>) -> FilterResult<()> {
+    let bytes = lexer.remainder().as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [b'{', b'-', ..] => {
+                depth += 1;
+                i += 2;
+            }
+
+            [b'-', b'}', ..] => {
+                depth -= 1;
+                i += 2;
+
+                if depth == 0 {
+                    lexer.bump(i);
+                    return FilterResult::Emit(());
+                }
+            }
+
+            _ => i += 1,
+        }
+    }
+
+    let opener = lexer.span();
+    lexer.bump(bytes.len());
+    lexer.extras.push((LexError::UnterminatedComment, opener));
+
+    FilterResult::Emit(())
+}
+
 fn string_content(lexer: &Lexer<Token>) -> String {
     let slice = lexer.slice();
     slice[1..slice.len() - 1].to_string()
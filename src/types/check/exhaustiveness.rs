@@ -0,0 +1,208 @@
+//! Exhaustiveness and redundancy checking for `case` statements, based on the
+//! usefulness algorithm from Maranget's "Warnings for pattern matching". Only
+//! variants are closed, so only they can be matched exhaustively without a
+//! catch-all arm. Since the fields of a class come after those of the variant
+//! it is a case of, a pattern for a variant applies directly to its cases.
+
+use super::Checker;
+use crate::hir;
+use crate::names::Name;
+use crate::rst::ClassKind;
+use crate::source::Span;
+use crate::types::{Type, TypeNode};
+
+/// A simplified pattern.
+#[derive(Clone, Debug)]
+enum Pat {
+    Wild,
+    Class(Name, Vec<Pat>),
+}
+
+impl From<&hir::Pattern> for Pat {
+    fn from(pattern: &hir::Pattern) -> Self {
+        match &pattern.node {
+            hir::PatternNode::Class(class, fields) => {
+                Self::Class(*class, fields.iter().map(Self::from).collect())
+            }
+
+            // Invalid patterns have already been reported, so pretend they
+            // match anything.
+            hir::PatternNode::Binding(_) | hir::PatternNode::Invalid => Self::Wild,
+        }
+    }
+}
+
+impl Checker<'_> {
+    /// Report the cases of the scrutinee type not covered by any arm, as well
+    /// as arms which can never be reached. Returns whether every value of the
+    /// scrutinee type is matched by some arm.
+    pub(super) fn check_exhaustiveness(&self, span: Span, ty: Type, arms: &[hir::CaseArm]) -> bool {
+        let ty = self.deref(ty);
+        let mut rows: Vec<Vec<Pat>> = Vec::with_capacity(arms.len());
+
+        for arm in arms {
+            let row = vec![Pat::from(&arm.pattern)];
+            if !self.useful(&rows, &row, &[ty]) {
+                self.at(arm.pattern.span).types_unreachable_arm();
+            }
+
+            rows.push(row);
+        }
+
+        let exhaustive = !self.useful(&rows, &[Pat::Wild], &[ty]);
+        let Some(cases) = self.cases(ty) else {
+            return exhaustive;
+        };
+
+        let missing: Vec<_> = cases
+            .into_iter()
+            .filter(|case| {
+                let row = vec![self.constructor(*case, ty)];
+                self.useful(&rows, &row, &[ty])
+            })
+            .map(|case| Type::new(self.db, TypeNode::Name(case)))
+            .collect();
+
+        if !missing.is_empty() {
+            self.at(span).types_missing_arms(missing);
+        }
+
+        exhaustive
+    }
+
+    /// Is there any value matched by `row` which isn't matched by any row of
+    /// `matrix`? Each column has the corresponding type of `types`.
+    fn useful(&self, matrix: &[Vec<Pat>], row: &[Pat], types: &[Type]) -> bool {
+        let Some((first, rest)) = row.split_first() else {
+            return matrix.is_empty();
+        };
+
+        let ty = types[0];
+        match first {
+            Pat::Class(class, _) => self
+                .instances(*class, ty)
+                .into_iter()
+                .any(|instance| self.useful_for(matrix, row, types, instance)),
+
+            Pat::Wild => {
+                let mentioned = matrix
+                    .iter()
+                    .any(|row| matches!(row.first(), Some(Pat::Class(..))));
+
+                match self.cases(ty) {
+                    Some(cases) if mentioned => cases
+                        .into_iter()
+                        .any(|case| self.useful_for(matrix, row, types, case)),
+
+                    // If no arm looks at this column, or if there are too many
+                    // cases to enumerate, only the catch-alls matter.
+                    _ => {
+                        let matrix: Vec<_> = matrix
+                            .iter()
+                            .filter(|row| matches!(row.first(), Some(Pat::Wild)))
+                            .map(|row| row[1..].to_vec())
+                            .collect();
+
+                        self.useful(&matrix, rest, &types[1..])
+                    }
+                }
+            }
+        }
+    }
+
+    /// Is `row` useful with regards to `matrix` for instances of `class`?
+    fn useful_for(&self, matrix: &[Vec<Pat>], row: &[Pat], types: &[Type], class: Name) -> bool {
+        let fields: Vec<_> = self
+            .field_types(Type::new(self.db, TypeNode::Name(class)), Some(types[0]))
+            .into_iter()
+            .map(|(_, ty)| ty)
+            .collect();
+
+        let matrix: Vec<_> = matrix
+            .iter()
+            .filter_map(|row| self.specialize(row, class, fields.len()))
+            .collect();
+
+        let Some(row) = self.specialize(row, class, fields.len()) else {
+            return false;
+        };
+
+        let types: Vec<_> = fields
+            .into_iter()
+            .chain(types[1..].iter().copied())
+            .collect();
+        self.useful(&matrix, &row, &types)
+    }
+
+    /// Specialize a row for instances of `class`, which has `arity` fields,
+    /// returning `None` if the first pattern can't match them.
+    fn specialize(&self, row: &[Pat], class: Name, arity: usize) -> Option<Vec<Pat>> {
+        let (first, rest) = row.split_first()?;
+        let mut fields = match first {
+            Pat::Wild => Vec::new(),
+            Pat::Class(matched, fields) if self.is_instance(class, *matched) => fields.clone(),
+            Pat::Class(..) => return None,
+        };
+
+        fields.resize(arity, Pat::Wild);
+        fields.extend(rest.iter().cloned());
+        Some(fields)
+    }
+
+    /// The classes whose instances a pattern for `class` can match, within a
+    /// column of type `ty`. Every case of a variant is considered separately.
+    fn instances(&self, class: Name, ty: Type) -> Vec<Name> {
+        match self.cases(ty) {
+            Some(cases) => cases
+                .into_iter()
+                .filter(|case| self.is_instance(*case, class))
+                .collect(),
+            None => vec![class],
+        }
+    }
+
+    /// The classes every value of type `ty` is an instance of exactly one of,
+    /// or `None` if `ty` is not a variant.
+    fn cases(&self, ty: Type) -> Option<Vec<Name>> {
+        let (TypeNode::Name(class) | TypeNode::Applied(class, _)) = self.deref(ty).node(self.db)
+        else {
+            return None;
+        };
+
+        let variant = self.classes.get(&class)?;
+        if variant.kind != ClassKind::Variant || variant.fields.classes.is_empty() {
+            return None;
+        }
+
+        let mut cases = Vec::new();
+        self.collect_cases(class, &mut cases);
+        Some(cases)
+    }
+
+    fn collect_cases(&self, class: Name, cases: &mut Vec<Name>) {
+        let declaration = &self.classes[&class];
+        if declaration.kind == ClassKind::Variant && !declaration.fields.classes.is_empty() {
+            for nested in declaration.fields.classes.iter() {
+                self.collect_cases(*nested, cases);
+            }
+        } else {
+            cases.push(class);
+        }
+    }
+
+    /// A pattern matching every instance of `class`.
+    fn constructor(&self, class: Name, within: Type) -> Pat {
+        let arity = self
+            .field_types(Type::new(self.db, TypeNode::Name(class)), Some(within))
+            .len();
+
+        Pat::Class(class, vec![Pat::Wild; arity])
+    }
+
+    /// Is every instance of `class` an instance of `of`?
+    fn is_instance(&self, class: Name, of: Name) -> bool {
+        let class = Type::new(self.db, TypeNode::Name(class));
+        let of = Type::new(self.db, TypeNode::Name(of));
+        self.info.subtypes(self.db).is_class_subtype(&class, &of)
+    }
+}
@@ -0,0 +1,862 @@
+use std::collections::HashMap;
+
+use super::Checker;
+use crate::ast::{BinaryOperator, UnaryOperator};
+use crate::hir;
+use crate::names::{Name, NameNode, NamePart};
+use crate::rst;
+use crate::source::Span;
+use crate::types::generics::instance;
+use crate::types::{check_number_literal, suffix_type, Substitution, Type, TypeNode};
+
+impl Checker<'_> {
+    /// Check that `expr` has a type conforming to `expected`, using it to guide
+    /// the checking of lambdas, literals and generic calls.
+    pub(super) fn check(&mut self, expr: &rst::Expression, expected: Type) -> hir::Expression {
+        match (&expr.node, expected.node(self.db)) {
+            (rst::ExpressionNode::Lambda(lambda), TypeNode::Function(params, ret)) => {
+                self.lambda(expr.span, lambda, Some((params, ret)))
+            }
+
+            (rst::ExpressionNode::Number(literal), TypeNode::Int | TypeNode::Nat)
+                if literal.suffix.is_none() =>
+            {
+                check_number_literal(self.db, expr.span, literal, false, expected);
+
+                hir::Expression {
+                    node: hir::ExpressionNode::Number(literal.clone()),
+                    span: expr.span,
+                    anno: expected,
+                }
+            }
+
+            (
+                rst::ExpressionNode::Unary(UnaryOperator::Negate, inner),
+                TypeNode::Int | TypeNode::Nat,
+            ) => match &inner.node {
+                rst::ExpressionNode::Number(literal) if literal.suffix.is_none() => {
+                    check_number_literal(self.db, expr.span, literal, true, expected);
+
+                    let inner = hir::Expression {
+                        node: hir::ExpressionNode::Number(literal.clone()),
+                        span: inner.span,
+                        anno: expected,
+                    };
+
+                    hir::Expression {
+                        node: hir::ExpressionNode::Unary(UnaryOperator::Negate, Box::new(inner)),
+                        span: expr.span,
+                        anno: expected,
+                    }
+                }
+
+                _ => self.subsumed(expr, expected),
+            },
+
+            _ => self.subsumed(expr, expected),
+        }
+    }
+
+    /// Figure out the type of `expr` on its own.
+    pub(super) fn infer(&mut self, expr: &rst::Expression) -> hir::Expression {
+        self.synthesize(expr, None)
+    }
+
+    fn subsumed(&mut self, expr: &rst::Expression, expected: Type) -> hir::Expression {
+        let result = self.synthesize(expr, Some(expected));
+        self.subsume(result.span, result.anno, expected);
+        result
+    }
+
+    /// Infer the type of `expr`, possibly using the type it is expected to have
+    /// to infer the type arguments of generic calls.
+    fn synthesize(&mut self, expr: &rst::Expression, expected: Option<Type>) -> hir::Expression {
+        let span = expr.span;
+        let (node, anno) = match &expr.node {
+            rst::ExpressionNode::Reference(inner) => {
+                let inner = self.infer(inner);
+                let anno = Type::new(self.db, TypeNode::Reference(inner.anno));
+                (hir::ExpressionNode::Reference(Box::new(inner)), anno)
+            }
+
+            rst::ExpressionNode::Binary(op, lhs, rhs) => {
+                return self.binary(span, *op, lhs, rhs);
+            }
+
+            rst::ExpressionNode::Unary(op, operand) => return self.unary(span, *op, operand),
+
+            rst::ExpressionNode::Call(callee, args) => {
+                if let Some(class) = self.constructed_class(callee) {
+                    return self.construct(span, class, args, expected);
+                }
+
+                let (callee, params) = self.callee(callee);
+                return self.call(span, callee, params, args, expected);
+            }
+
+            rst::ExpressionNode::Field(..) | rst::ExpressionNode::Name(_) => {
+                let (expr, params) = self.callee(expr);
+                if params.is_empty() {
+                    return expr;
+                }
+
+                // A generic function used as a value needs to know what type
+                // it is expected to have.
+                let mut bindings = HashMap::new();
+                if let Some(expected) = expected {
+                    self.unify(expr.anno, expected, &params, &mut bindings);
+                }
+
+                let subst = self.bindings(span, &params, bindings);
+                let anno = subst.apply(self.db, &expr.anno);
+                return hir::Expression { anno, ..expr };
+            }
+
+            rst::ExpressionNode::Number(literal) => {
+                let ty = suffix_type(self.db, literal).unwrap_or_else(|| self.int());
+                check_number_literal(self.db, span, literal, false, ty);
+                (hir::ExpressionNode::Number(literal.clone()), ty)
+            }
+
+            rst::ExpressionNode::String(string) => (
+                hir::ExpressionNode::String(string.clone()),
+                self.string_type(span),
+            ),
+
+            rst::ExpressionNode::Interpolated(parts) => {
+                let parts = parts
+                    .iter()
+                    .map(|part| match part {
+                        rst::StringPart::Text(text) => hir::StringPart::Text(text.clone()),
+                        rst::StringPart::Expression(expr) => {
+                            let expr = self.infer(expr);
+                            hir::StringPart::Expression(self.stringify(expr))
+                        }
+                    })
+                    .collect();
+
+                (
+                    hir::ExpressionNode::Interpolated(parts),
+                    self.string_type(span),
+                )
+            }
+
+            rst::ExpressionNode::This => match self.this {
+                Some(ty) => (hir::ExpressionNode::This, ty),
+                None => {
+                    self.at(span).types_this_outside_method();
+                    (hir::ExpressionNode::This, self.bottom())
+                }
+            },
+
+            rst::ExpressionNode::Unit => (hir::ExpressionNode::Unit, self.unit()),
+
+            rst::ExpressionNode::Lambda(lambda) => return self.lambda(span, lambda, None),
+
+            rst::ExpressionNode::Invalid => (hir::ExpressionNode::Invalid, self.bottom()),
+        };
+
+        hir::Expression { node, span, anno }
+    }
+
+    /// Check a name or a field access, returning it along with the type
+    /// parameters of the generic function it refers to, if any.
+    fn callee(&mut self, expr: &rst::Expression) -> (hir::Expression, Vec<Name>) {
+        let span = expr.span;
+        match &expr.node {
+            rst::ExpressionNode::Name(name) => {
+                if let Some(ty) = self.locals.get(name) {
+                    let expr = hir::Expression {
+                        node: hir::ExpressionNode::Name(*name),
+                        span,
+                        anno: *ty,
+                    };
+
+                    return (expr, Vec::new());
+                }
+
+                let (anno, params) = match self.values.get(name) {
+                    Some(signature) => (signature.ty, signature.type_params.clone()),
+                    None => {
+                        if self.classes.contains_key(name) {
+                            self.at(span).types_class_as_value();
+                        }
+
+                        (self.bottom(), Vec::new())
+                    }
+                };
+
+                let expr = hir::Expression {
+                    node: hir::ExpressionNode::Name(*name),
+                    span,
+                    anno,
+                };
+
+                (expr, params)
+            }
+
+            rst::ExpressionNode::Field(of, member) => {
+                if let Some(class) = self.class_name(of) {
+                    return self.static_member(span, class, *member);
+                }
+
+                let of = self.infer(of);
+                self.field(span, of, *member)
+            }
+
+            _ => (self.infer(expr), Vec::new()),
+        }
+    }
+
+    /// Access a member of a class through the class itself. Methods taking
+    /// `this` get it as their first argument.
+    fn static_member(
+        &mut self,
+        span: Span,
+        class: Name,
+        part: NamePart,
+    ) -> (hir::Expression, Vec<Name>) {
+        let invalid = |this: &Self| hir::Expression {
+            node: hir::ExpressionNode::Invalid,
+            span,
+            anno: this.bottom(),
+        };
+
+        if self.nested_class(span, class, part).is_some() {
+            self.at(span).types_class_as_value();
+            return (invalid(self), Vec::new());
+        }
+
+        let Some(member) = self.find_member(class, part) else {
+            let class = Type::new(self.db, TypeNode::Name(class));
+            self.at(span).types_no_such_member(class, part);
+            return (invalid(self), Vec::new());
+        };
+
+        self.check_visible(span, member);
+
+        let signature = &self.values[&member];
+        let anno = match (signature.this, signature.ty.node(self.db)) {
+            (Some(refs), TypeNode::Function(args, ret)) => {
+                let owner = signature.owner.unwrap_or(class);
+                let this = self.this_type(owner, refs);
+                let args = std::iter::once(this).chain(args).collect();
+                Type::new(self.db, TypeNode::Function(args, ret))
+            }
+
+            _ => signature.ty,
+        };
+
+        let expr = hir::Expression {
+            node: hir::ExpressionNode::Name(member),
+            span,
+            anno,
+        };
+
+        (expr, signature.type_params.clone())
+    }
+
+    /// Access a member of the value `of`.
+    fn field(
+        &mut self,
+        span: Span,
+        of: hir::Expression,
+        part: NamePart,
+    ) -> (hir::Expression, Vec<Name>) {
+        let ty = self.deref(of.anno);
+        let found = match ty.node(self.db) {
+            TypeNode::Name(class) | TypeNode::Applied(class, _) => self.find_member(class, part),
+            _ => None,
+        };
+
+        let Some(member) = found else {
+            if ty.node(self.db) != TypeNode::Bottom {
+                self.at(span).types_no_such_member(ty, part);
+            }
+
+            let expr = hir::Expression {
+                node: hir::ExpressionNode::Invalid,
+                span,
+                anno: self.bottom(),
+            };
+
+            return (expr, Vec::new());
+        };
+
+        self.check_visible(span, member);
+        let anno = self.member_type(ty, member);
+        let params = self.values[&member].type_params.clone();
+        let expr = hir::Expression {
+            node: hir::ExpressionNode::Field(Box::new(of), member),
+            span,
+            anno,
+        };
+
+        (expr, params)
+    }
+
+    /// Call `callee`, inferring the type arguments for `params` if it is
+    /// generic.
+    fn call(
+        &mut self,
+        span: Span,
+        callee: hir::Expression,
+        params: Vec<Name>,
+        args: &[rst::Expression],
+        expected: Option<Type>,
+    ) -> hir::Expression {
+        let TypeNode::Function(arg_types, ret) = callee.anno.node(self.db) else {
+            if callee.anno.node(self.db) != TypeNode::Bottom {
+                self.at(callee.span).types_not_callable(callee.anno);
+            }
+
+            let args = args.iter().map(|arg| self.infer(arg)).collect();
+            return hir::Expression {
+                node: hir::ExpressionNode::Call(Box::new(callee), args),
+                span,
+                anno: self.bottom(),
+            };
+        };
+
+        if arg_types.len() != args.len() {
+            self.at(span)
+                .types_wrong_argument_count(arg_types.len(), args.len());
+        }
+
+        let (args, ret) = self.arguments(span, &params, &arg_types, ret, args, expected);
+        hir::Expression {
+            node: hir::ExpressionNode::Call(Box::new(callee), args),
+            span,
+            anno: ret,
+        }
+    }
+
+    /// Check the arguments of a call to something taking `arg_types` and
+    /// returning `ret`. If there are any type `params`, they are inferred from
+    /// what the call is `expected` to return and from the arguments.
+    fn arguments(
+        &mut self,
+        span: Span,
+        params: &[Name],
+        arg_types: &[Type],
+        ret: Type,
+        args: &[rst::Expression],
+        expected: Option<Type>,
+    ) -> (Vec<hir::Expression>, Type) {
+        if params.is_empty() {
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(index, arg)| match arg_types.get(index) {
+                    Some(ty) => self.check(arg, *ty),
+                    None => self.infer(arg),
+                })
+                .collect();
+
+            return (args, ret);
+        }
+
+        // What the call is expected to return is the most reliable guide, as
+        // it lets arguments like `List.Nil()` be checked rather than inferred.
+        let mut bindings = HashMap::new();
+        if let Some(expected) = expected {
+            if expected.node(self.db) != TypeNode::Bottom {
+                self.unify(ret, expected, params, &mut bindings);
+            }
+        }
+
+        // Arguments whose types are fully known by now are checked against
+        // them, while the rest are inferred and add to the known bindings.
+        // Lambdas without annotations can only be checked once their argument
+        // types are known, so they wait until the end.
+        let mut checked = Vec::with_capacity(args.len());
+        for (index, arg) in args.iter().enumerate() {
+            let Some(ty) = arg_types.get(index) else {
+                checked.push(Some((self.infer(arg), true)));
+                continue;
+            };
+
+            let partial = self.partial(&bindings);
+            let ty = partial.apply(self.db, ty);
+
+            if !self.mentions(ty, params) {
+                checked.push(Some((self.check(arg, ty), true)));
+            } else if needs_expected(arg) {
+                checked.push(None);
+            } else {
+                let arg = self.infer(arg);
+                self.unify(ty, arg.anno, params, &mut bindings);
+                checked.push(Some((arg, false)));
+            }
+        }
+
+        let subst = self.bindings(span, params, bindings);
+
+        let args = args
+            .iter()
+            .zip(checked)
+            .enumerate()
+            .map(|(index, (arg, checked))| {
+                let ty = arg_types.get(index).map(|ty| subst.apply(self.db, ty));
+                match (checked, ty) {
+                    (Some((arg, false)), Some(ty)) => {
+                        self.subsume(arg.span, arg.anno, ty);
+                        arg
+                    }
+
+                    (Some((arg, _)), _) => arg,
+                    (None, Some(ty)) => self.check(arg, ty),
+                    (None, None) => self.infer(arg),
+                }
+            })
+            .collect();
+
+        (args, subst.apply(self.db, &ret))
+    }
+
+    /// Create an instance of `class`, which must not have any abstract members.
+    /// Its constructor is treated as a generic function over the type
+    /// parameters of the class, as well as those of the variant it is a case
+    /// of.
+    fn construct(
+        &mut self,
+        span: Span,
+        class: Name,
+        args: &[rst::Expression],
+        expected: Option<Type>,
+    ) -> hir::Expression {
+        let missing = self.abstract_members(class);
+        if !missing.is_empty() {
+            let class = Type::new(self.db, TypeNode::Name(class));
+            self.at(span).types_abstract_instance(class, missing);
+        }
+
+        let subtypes = self.info.subtypes(self.db);
+        let own: Vec<_> = subtypes.params(&class).iter().map(|(p, _)| *p).collect();
+        let variant = self.enclosing_variant(class);
+        let outer: Vec<_> = variant
+            .iter()
+            .flat_map(|variant| subtypes.params(variant).iter().map(|(p, _)| *p))
+            .collect();
+
+        let as_types = |params: &[Name]| -> Vec<Type> {
+            params
+                .iter()
+                .map(|param| Type::new(self.db, TypeNode::Name(*param)))
+                .collect()
+        };
+
+        let fields = self.field_types(instance(self.db, class, &as_types(&own)), None);
+        let arg_types: Vec<_> = fields.iter().map(|(_, ty)| *ty).collect();
+
+        // Cases of a generic variant are only ever seen as the variant itself.
+        let ret = match variant {
+            Some(variant) if !outer.is_empty() => instance(self.db, variant, &as_types(&outer)),
+            _ => instance(self.db, class, &as_types(&own)),
+        };
+
+        if arg_types.len() != args.len() {
+            self.at(span)
+                .types_wrong_argument_count(arg_types.len(), args.len());
+        }
+
+        let params: Vec<_> = own.into_iter().chain(outer).collect();
+        let (args, anno) = self.arguments(span, &params, &arg_types, ret, args, expected);
+
+        hir::Expression {
+            node: hir::ExpressionNode::Construct(class, args),
+            span,
+            anno,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        span: Span,
+        op: BinaryOperator,
+        lhs: &rst::Expression,
+        rhs: &rst::Expression,
+    ) -> hir::Expression {
+        // In something like `5 + x`, the literal gets the type of `x`.
+        let (lhs, rhs) = match &lhs.node {
+            rst::ExpressionNode::Number(literal) if literal.suffix.is_none() => {
+                let rhs = self.infer(rhs);
+                let lhs = match rhs.anno.node(self.db) {
+                    TypeNode::Int | TypeNode::Nat => self.check(lhs, rhs.anno),
+                    _ => self.infer(lhs),
+                };
+
+                (lhs, Ok(rhs))
+            }
+
+            _ => (self.infer(lhs), Err(rhs)),
+        };
+
+        let anno = match (lhs.anno.node(self.db), op) {
+            (TypeNode::Int | TypeNode::Nat, BinaryOperator::Less)
+            | (TypeNode::Int | TypeNode::Nat, BinaryOperator::Equal)
+            | (TypeNode::Int | TypeNode::Nat, BinaryOperator::Greater)
+            | (TypeNode::Boolean, BinaryOperator::Equal) => {
+                Some(Type::new(self.db, TypeNode::Boolean))
+            }
+
+            (TypeNode::Int | TypeNode::Nat | TypeNode::Bottom, _) => Some(lhs.anno),
+            _ => None,
+        };
+
+        let Some(anno) = anno else {
+            let args = match rhs {
+                Ok(rhs) => vec![rhs],
+                Err(rhs) => vec![self.infer(rhs)],
+            };
+
+            return self.operator_call(span, lhs, op.name(self.db), op.function_name(), args);
+        };
+
+        let rhs = match rhs {
+            Ok(rhs) => {
+                self.subsume(rhs.span, rhs.anno, lhs.anno);
+                rhs
+            }
+
+            Err(rhs) => self.check(rhs, lhs.anno),
+        };
+
+        hir::Expression {
+            node: hir::ExpressionNode::Binary(op, Box::new(lhs), Box::new(rhs)),
+            span,
+            anno,
+        }
+    }
+
+    fn unary(
+        &mut self,
+        span: Span,
+        op: UnaryOperator,
+        operand: &rst::Expression,
+    ) -> hir::Expression {
+        let operand = match &operand.node {
+            rst::ExpressionNode::Number(literal) if literal.suffix.is_none() => {
+                let ty = self.int();
+                check_number_literal(self.db, operand.span, literal, true, ty);
+
+                hir::Expression {
+                    node: hir::ExpressionNode::Number(literal.clone()),
+                    span: operand.span,
+                    anno: ty,
+                }
+            }
+
+            _ => self.infer(operand),
+        };
+
+        match (operand.anno.node(self.db), op) {
+            (TypeNode::Int | TypeNode::Bottom, UnaryOperator::Negate) => {
+                let anno = operand.anno;
+                hir::Expression {
+                    node: hir::ExpressionNode::Unary(op, Box::new(operand)),
+                    span,
+                    anno,
+                }
+            }
+
+            _ => self.operator_call(
+                span,
+                operand,
+                op.name(self.db),
+                op.function_name(),
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// Call the operator function `name` on `this` with the already checked
+    /// `args`.
+    fn operator_call(
+        &mut self,
+        span: Span,
+        this: hir::Expression,
+        name: NamePart,
+        operator: &str,
+        args: Vec<hir::Expression>,
+    ) -> hir::Expression {
+        let ty = self.deref(this.anno);
+        let member = match ty.node(self.db) {
+            TypeNode::Name(class) | TypeNode::Applied(class, _) => self.find_member(class, name),
+            _ => None,
+        };
+
+        let Some(member) = member else {
+            self.at(span).types_no_operator(ty, operator);
+            let mut exprs = vec![this];
+            exprs.extend(args);
+
+            return hir::Expression {
+                node: hir::ExpressionNode::Call(
+                    Box::new(hir::Expression {
+                        node: hir::ExpressionNode::Invalid,
+                        span,
+                        anno: self.bottom(),
+                    }),
+                    exprs,
+                ),
+                span,
+                anno: self.bottom(),
+            };
+        };
+
+        self.check_visible(span, member);
+        let anno = self.member_type(ty, member);
+        let callee = hir::Expression {
+            node: hir::ExpressionNode::Field(Box::new(this), member),
+            span,
+            anno,
+        };
+
+        let TypeNode::Function(params, ret) = anno.node(self.db) else {
+            self.at(span).types_not_callable(anno);
+            return hir::Expression {
+                node: hir::ExpressionNode::Call(Box::new(callee), args),
+                span,
+                anno: self.bottom(),
+            };
+        };
+
+        if params.len() != args.len() {
+            self.at(span)
+                .types_wrong_argument_count(params.len(), args.len());
+        }
+
+        for (arg, ty) in args.iter().zip(params) {
+            self.subsume(arg.span, arg.anno, ty);
+        }
+
+        hir::Expression {
+            node: hir::ExpressionNode::Call(Box::new(callee), args),
+            span,
+            anno: ret,
+        }
+    }
+
+    /// Convert an interpolated expression to a string by calling `to_string`
+    /// on it, which it has if it is `Stringable`. Builtin types are converted
+    /// by the runtime instead.
+    fn stringify(&mut self, expr: hir::Expression) -> hir::Expression {
+        let ty = self.deref(expr.anno);
+        if let TypeNode::Int | TypeNode::Nat | TypeNode::Boolean = ty.node(self.db) {
+            let span = expr.span;
+            return hir::Expression {
+                node: hir::ExpressionNode::ToString(Box::new(expr)),
+                span,
+                anno: self.string_type(span),
+            };
+        }
+
+        let stringable = self.stringable_type(expr.span);
+        self.subsume(expr.span, ty, stringable);
+
+        // Either way, the problem has already been reported
+        let invalid = |ty: Type| ty.node(self.db) == TypeNode::Bottom;
+        if invalid(ty) || invalid(stringable) || !self.conforms(ty, stringable) {
+            return expr;
+        }
+
+        let name = NamePart::new(self.db, NameNode::Value("to_string".into()));
+        self.operator_call(expr.span, expr, name, "to_string", Vec::new())
+    }
+
+    /// Check a lambda, possibly against the argument and return types of the
+    /// function type it is expected to have.
+    fn lambda(
+        &mut self,
+        span: Span,
+        lambda: &rst::Lambda,
+        expected: Option<(Vec<Type>, Type)>,
+    ) -> hir::Expression {
+        if let Some((params, _)) = &expected {
+            if params.len() != lambda.params.len() {
+                self.at(span)
+                    .types_wrong_argument_count(params.len(), lambda.params.len());
+            }
+        }
+
+        let mut params = Vec::with_capacity(lambda.params.len());
+        for (index, (name, anno)) in lambda.params.iter().enumerate() {
+            let expected = expected
+                .as_ref()
+                .and_then(|(params, _)| params.get(index).copied());
+
+            let ty = match (anno, expected) {
+                (Some(anno), Some(expected)) => {
+                    let ty = self.lower(anno);
+                    self.subsume(anno.span, expected, ty);
+                    ty
+                }
+
+                (Some(anno), None) => self.lower(anno),
+                (None, Some(expected)) => expected,
+                (None, None) => {
+                    self.at(span).types_cannot_infer();
+                    self.bottom()
+                }
+            };
+
+            self.locals.insert(*name, ty);
+            params.push(ty);
+        }
+
+        let ret = match (&lambda.return_type, expected) {
+            (Some(ty), _) => self.lower(ty),
+            (None, Some((_, ret))) => ret,
+            (None, None) => self.unit(),
+        };
+
+        let returns = self.returns.replace(ret);
+        let body = self.check_block(&lambda.body);
+        self.returns = returns;
+
+        hir::Expression {
+            node: hir::ExpressionNode::Lambda {
+                args: lambda.params.iter().map(|(name, _)| *name).collect(),
+                body,
+                captures: lambda.captures.clone(),
+            },
+            span,
+            anno: Type::new(self.db, TypeNode::Function(params, ret)),
+        }
+    }
+
+    /// Bind the type parameters in `pattern` to the corresponding parts of
+    /// `ty`. Parameters which are already bound keep their first binding.
+    fn unify(&self, pattern: Type, ty: Type, params: &[Name], bindings: &mut HashMap<Name, Type>) {
+        match (pattern.node(self.db), ty.node(self.db)) {
+            (TypeNode::Name(param), _) if params.contains(&param) => {
+                bindings.entry(param).or_insert(ty);
+            }
+
+            (TypeNode::Applied(class, patterns), _) => {
+                let target = Type::new(self.db, TypeNode::Name(class));
+                let Some(path) = self
+                    .info
+                    .subtypes(self.db)
+                    .supertype_path(self.db, &ty, &target)
+                else {
+                    return;
+                };
+
+                if let Some(TypeNode::Applied(_, args)) = path.first().map(|ty| ty.node(self.db)) {
+                    for (pattern, arg) in patterns.into_iter().zip(args) {
+                        self.unify(pattern, arg, params, bindings);
+                    }
+                }
+            }
+
+            (TypeNode::Function(patterns, ret_pattern), TypeNode::Function(args, ret)) => {
+                for (pattern, arg) in patterns.into_iter().zip(args) {
+                    self.unify(pattern, arg, params, bindings);
+                }
+
+                self.unify(ret_pattern, ret, params, bindings);
+            }
+
+            (TypeNode::Reference(pattern), TypeNode::Reference(ty)) => {
+                self.unify(pattern, ty, params, bindings);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// The substitution for the type parameters bound so far.
+    fn partial(&self, bindings: &HashMap<Name, Type>) -> Substitution {
+        let (params, args): (Vec<_>, Vec<_>) = bindings.iter().map(|(p, t)| (*p, *t)).unzip();
+        Substitution::new(&params, &args)
+    }
+
+    /// Does `ty` mention any of the given type parameters?
+    fn mentions(&self, ty: Type, params: &[Name]) -> bool {
+        match ty.node(self.db) {
+            TypeNode::Name(name) => params.contains(&name),
+            TypeNode::Applied(_, args) => args.iter().any(|ty| self.mentions(*ty, params)),
+            TypeNode::Function(args, ret) => {
+                args.iter().any(|ty| self.mentions(*ty, params)) || self.mentions(ret, params)
+            }
+
+            TypeNode::Reference(of) => self.mentions(of, params),
+            TypeNode::Bottom
+            | TypeNode::Unit
+            | TypeNode::Int
+            | TypeNode::Nat
+            | TypeNode::Boolean => false,
+        }
+    }
+
+    /// Turn the inferred bindings of `params` into a substitution, reporting
+    /// an error for any which could not be inferred.
+    fn bindings(&self, span: Span, params: &[Name], bindings: HashMap<Name, Type>) -> Substitution {
+        let args: Vec<_> = params
+            .iter()
+            .map(|param| {
+                bindings.get(param).copied().unwrap_or_else(|| {
+                    self.at(span).types_cannot_infer();
+                    self.bottom()
+                })
+            })
+            .collect();
+
+        Substitution::new(params, &args)
+    }
+
+    /// If `callee` names a class, the class it names.
+    fn constructed_class(&self, callee: &rst::Expression) -> Option<Name> {
+        match &callee.node {
+            rst::ExpressionNode::Name(name) if self.classes.contains_key(name) => Some(*name),
+            rst::ExpressionNode::Field(of, part) => {
+                let class = self.class_name(of)?;
+                self.nested_class(callee.span, class, *part)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// The class the expression `of` refers to, if it is a name or a path to a
+    /// nested class.
+    fn class_name(&self, of: &rst::Expression) -> Option<Name> {
+        match &of.node {
+            rst::ExpressionNode::Name(name) if self.classes.contains_key(name) => Some(*name),
+            rst::ExpressionNode::Field(inner, part) => {
+                let class = self.class_name(inner)?;
+                self.nested_class(of.span, class, *part)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// The class called `part` nested within `class`, if there is one. It is
+    /// an error for it to be private and not visible here.
+    fn nested_class(&self, span: Span, class: Name, part: NamePart) -> Option<Name> {
+        let nested = self
+            .info
+            .nested(self.db)
+            .get(&class)
+            .and_then(|nested| nested.get(&part))
+            .copied()?;
+
+        self.check_visible(span, nested);
+        Some(nested)
+    }
+
+    fn int(&self) -> Type {
+        Type::new(self.db, TypeNode::Int)
+    }
+}
+
+/// Does this expression need to know what type it should have to be checked?
+fn needs_expected(expr: &rst::Expression) -> bool {
+    match &expr.node {
+        rst::ExpressionNode::Lambda(lambda) => lambda.params.iter().any(|(_, ty)| ty.is_none()),
+        _ => false,
+    }
+}
@@ -0,0 +1,127 @@
+use super::Checker;
+use crate::names::{Name, NamePart, NamePrefix};
+use crate::rst::{self, ClassKind, DeclarationName};
+use crate::types::{Type, TypeNode};
+
+impl Checker<'_> {
+    /// Find the member with the given name in `class` or any of its
+    /// supertypes.
+    pub(super) fn find_member(&self, class: Name, name: NamePart) -> Option<Name> {
+        self.members
+            .lookup(self.db, class, name)
+            .map(|member| member.name)
+    }
+
+    /// The type of `member` when accessed through a value of type `of`.
+    pub(super) fn member_type(&self, of: Type, member: Name) -> Type {
+        let signature = &self.values[&member];
+        let Some(owner) = signature.owner else {
+            return signature.ty;
+        };
+
+        self.info
+            .member_type(self.db, &of, owner, &signature.ty)
+            .unwrap_or(signature.ty)
+    }
+
+    /// The fields of the class `ty` is an instance of, along with their types,
+    /// in the order they are given when creating an instance or matching on
+    /// one. The fields of its supertypes come first.
+    ///
+    /// Classes nested in a generic variant may mention the type parameters of
+    /// that variant. If the instance is known to be an instance of some
+    /// `within` type, those parameters are replaced with its arguments.
+    pub(super) fn field_types(&self, ty: Type, within: Option<Type>) -> Vec<(Name, Type)> {
+        let (TypeNode::Name(class) | TypeNode::Applied(class, _)) = ty.node(self.db) else {
+            return Vec::new();
+        };
+
+        let mut fields = Vec::new();
+        self.collect_fields(class, &mut fields);
+
+        let outer = within.map(|within| self.deref(within)).and_then(|within| {
+            let (TypeNode::Name(outer) | TypeNode::Applied(outer, _)) = within.node(self.db) else {
+                return None;
+            };
+
+            self.info.substitution(self.db, &within, outer)
+        });
+
+        fields
+            .into_iter()
+            .map(|field| {
+                let ty = self.member_type(ty, field);
+                let ty = match &outer {
+                    Some(outer) => outer.apply(self.db, &ty),
+                    None => ty,
+                };
+
+                (field, ty)
+            })
+            .collect()
+    }
+
+    /// Strip any references off a type.
+    pub(super) fn deref(&self, ty: Type) -> Type {
+        match ty.node(self.db) {
+            TypeNode::Reference(of) => self.deref(of),
+            _ => ty,
+        }
+    }
+
+    fn collect_fields(&self, class: Name, fields: &mut Vec<Name>) {
+        let parents = self
+            .enclosing_variant(class)
+            .into_iter()
+            .chain(self.classes[&class].inherits.iter().filter_map(class_name));
+
+        for parent in parents {
+            if self.classes.contains_key(&parent) {
+                self.collect_fields(parent, fields);
+            }
+        }
+
+        for value in self.classes[&class].fields.values.iter() {
+            let rst::ValueNode::Variable { .. } = value.node else {
+                continue;
+            };
+
+            let Some(name) = value.name.declared(self.db, NamePrefix::Item(class)) else {
+                continue;
+            };
+
+            // An overriding field takes the place of the one it overrides
+            let overridden = match value.name {
+                DeclarationName::Field(..) => fields
+                    .iter()
+                    .position(|field| field.name(self.db) == name.name(self.db)),
+                _ => None,
+            };
+
+            match overridden {
+                Some(index) => fields[index] = name,
+                None if !fields.contains(&name) => fields.push(name),
+                None => {}
+            }
+        }
+    }
+
+    /// The variant the given class is a case of, if any.
+    pub(super) fn enclosing_variant(&self, class: Name) -> Option<Name> {
+        self.classes
+            .iter()
+            .find(|(_, variant)| {
+                variant.kind == ClassKind::Variant && variant.fields.classes.contains(&class)
+            })
+            .map(|(name, _)| *name)
+    }
+}
+
+/// The class an inherited type refers to.
+fn class_name(ty: &rst::Type) -> Option<Name> {
+    match &ty.node {
+        rst::TypeNode::Name(name) => Some(*name),
+        rst::TypeNode::Applied(to, _) => class_name(to),
+        _ => None,
+    }
+}
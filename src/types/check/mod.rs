@@ -1,57 +1,547 @@
-#![allow(unused)]
+//! Bidirectional type checking, which turns the [`rst`] of a source into the
+//! fully annotated [`hir`].
 
-use std::collections::HashMap;
+mod exhaustiveness;
+mod expressions;
+mod members;
+mod statements;
 
-use super::Type;
-use crate::hir::{Class, Items, Value};
-use crate::names::Name;
-use crate::parse::parse;
-use crate::source::Source;
-use crate::{ast, Db};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use super::generics::instance;
+use super::lower::TypeLowerer;
+use super::{member_table, type_info, Member, MemberTable, Substitution, Type, TypeInfo, TypeNode};
+use crate::messages::MessageMaker;
+use crate::names::{Name, NameNode, NamePart, NamePrefix};
+use crate::resolution::{
+    lookup_top_level, resolve_imports, resolve_names, visible_classes, visible_names, NamesWithin,
+};
+use crate::rst::{self, ClassKind, DeclarationName};
+use crate::source::{Source, Span};
+use crate::{hir, Db};
 
 #[salsa::tracked]
-pub fn annotate(db: &dyn Db, source: Source) -> Items {
-    let mut classes = Vec::new();
-    let mut values = Vec::new();
+pub fn annotate(db: &dyn Db, source: Source) -> hir::Items {
+    let items = resolve_names(db, source).tree(db);
+    let classes = visible_classes(db, source);
+    let info = type_info(db, source);
+    let members = member_table(db, source);
+    let names = visible_names(db, source);
 
-    let tree = parse(db, source);
+    let mut checker = Checker::new(db, source, classes, info, members, names);
 
-    let mut checker = Checker::new(db);
-    for item in tree.declarations(db) {
-        match checker.check(item) {
-            ClassOrValue::Class(class) => classes.push(class),
-            ClassOrValue::Value(value) => values.push(value),
-        }
+    // The items of imported modules are only declared, since they are checked
+    // along with their own source.
+    for module in resolve_imports(db, source).all(db) {
+        let values: Vec<_> = resolve_names(db, *module)
+            .tree(db)
+            .values(db)
+            .iter()
+            .filter(|value| matches!(value.name, DeclarationName::Name(_)))
+            .cloned()
+            .collect();
+
+        checker.declare_values(&values, None);
+    }
+
+    checker.declare_values(items.values(db), None);
+    for (name, class) in classes {
+        checker.declare_values(&class.fields.values, Some(*name));
     }
 
-    Items::new(db, classes, values)
+    let values = items
+        .values(db)
+        .iter()
+        .filter_map(|value| checker.check_value(value, None))
+        .collect();
+
+    // Nested classes are checked along with the class they are nested in
+    let classes = items.classes(db);
+    let mut top_level: Vec<_> = classes
+        .iter()
+        .filter(|(name, _)| {
+            !classes
+                .values()
+                .any(|class| class.fields.classes.contains(name))
+        })
+        .map(|(name, class)| (class.span.start, *name))
+        .collect();
+    top_level.sort();
+
+    let classes = top_level
+        .into_iter()
+        .map(|(_, name)| checker.check_class(name))
+        .collect();
+
+    hir::Items::new(db, classes, values)
 }
 
-#[derive(Debug)]
-enum ClassOrValue {
-    Class(Class),
-    Value(Value),
+/// What the checker knows about an item or a member of a class.
+#[derive(Clone, Debug)]
+struct Signature {
+    /// The class this is a member of, if any.
+    owner: Option<Name>,
+    type_params: Vec<Name>,
+
+    /// `Some(n)` if this is a function which takes a `this` argument behind `n`
+    /// references.
+    this: Option<usize>,
+
+    /// Is this a variable (or a field, if it is a member)?
+    variable: bool,
+
+    /// The type of the value. For functions, this does not include `this`.
+    ty: Type,
+
+    span: Span,
 }
 
 struct Checker<'a> {
     db: &'a dyn Db,
-    context: HashMap<Name, Type>,
+    source: Source,
+    classes: &'a HashMap<Name, rst::Class>,
+    info: TypeInfo,
+    members: MemberTable,
+    names: NamesWithin,
+
+    values: HashMap<Name, Signature>,
+    locals: HashMap<Name, Type>,
+
+    /// The type of `this` within the current function, if it takes it.
+    this: Option<Type>,
+
+    /// The type the current function or lambda returns.
+    returns: Option<Type>,
+
+    /// The item being checked, which determines which private members are
+    /// visible.
+    item: Option<Name>,
+
+    /// The spans private names have been reported at, since paths to nested
+    /// classes may be looked at more than once.
+    private: RefCell<HashSet<Span>>,
 }
 
 impl<'a> Checker<'a> {
-    pub fn new(db: &'a dyn Db) -> Self {
+    pub fn new(
+        db: &'a dyn Db,
+        source: Source,
+        classes: &'a HashMap<Name, rst::Class>,
+        info: TypeInfo,
+        members: MemberTable,
+        names: NamesWithin,
+    ) -> Self {
         Self {
             db,
-            context: HashMap::new(),
+            source,
+            classes,
+            info,
+            members,
+            names,
+
+            values: HashMap::new(),
+            locals: HashMap::new(),
+
+            this: None,
+            returns: None,
+
+            item: None,
+            private: RefCell::new(HashSet::new()),
         }
     }
 
-    pub fn check(&mut self, item: &ast::Declaration) -> ClassOrValue {
-        todo!()
+    /// Figure out the types of the given items or members of a class, such that
+    /// they can be referred to before they are checked.
+    pub fn declare_values(&mut self, values: &[rst::Value], owner: Option<Name>) {
+        for value in values {
+            let Some(name) = value.name.declared(self.db, self.scope(owner)) else {
+                continue;
+            };
+
+            self.item = Some(name);
+            let signature = match &value.node {
+                rst::ValueNode::Function {
+                    type_params,
+                    this,
+                    args,
+                    return_type,
+                    ..
+                } => {
+                    let args = args.iter().map(|(_, ty)| self.lower(ty)).collect();
+                    let ret = self.lower(return_type);
+
+                    Signature {
+                        owner,
+                        type_params: type_params.clone(),
+                        this: *this,
+                        variable: false,
+                        ty: Type::new(self.db, TypeNode::Function(args, ret)),
+                        span: value.span,
+                    }
+                }
+
+                rst::ValueNode::Variable { anno, .. } => Signature {
+                    owner,
+                    type_params: Vec::new(),
+                    this: None,
+                    variable: true,
+                    ty: self.lower(anno),
+                    span: value.span,
+                },
+            };
+
+            self.values.insert(name, signature);
+        }
     }
-}
 
-enum ClassType {
-    Class,
-    Variant,
+    pub fn check_class(&mut self, name: Name) -> hir::Class {
+        let class = &self.classes[&name];
+        self.item = Some(name);
+
+        for value in class.fields.values.iter() {
+            match value.name {
+                DeclarationName::Field(parent, part) => {
+                    self.check_override(name, value, parent, part);
+                }
+
+                DeclarationName::Name(declared) => {
+                    self.check_implicit_override(name, value, declared.name(self.db));
+                }
+
+                DeclarationName::Invalid => {}
+            }
+        }
+
+        self.check_implemented(name, class);
+
+        let values = class
+            .fields
+            .values
+            .iter()
+            .filter_map(|value| self.check_value(value, Some(name)))
+            .collect();
+
+        let classes = class
+            .fields
+            .classes
+            .iter()
+            .map(|nested| self.check_class(*nested))
+            .collect();
+
+        hir::Class {
+            name,
+            items: hir::Items::new(self.db, classes, values),
+            open: class.kind == ClassKind::Class,
+            autoinherit: class.kind == ClassKind::Variant,
+        }
+    }
+
+    pub fn check_value(&mut self, value: &rst::Value, owner: Option<Name>) -> Option<hir::Value> {
+        if let (DeclarationName::Field(..), None) = (value.name, owner) {
+            self.at(value.span).types_override_outside_class();
+        }
+
+        let name = value.name.declared(self.db, self.scope(owner))?;
+
+        let signature = self.values[&name].clone();
+
+        self.item = Some(name);
+        self.locals.clear();
+        self.this = None;
+        self.returns = None;
+
+        let node = match &value.node {
+            rst::ValueNode::Function {
+                this, args, body, ..
+            } => {
+                let TypeNode::Function(params, ret) = signature.ty.node(self.db) else {
+                    unreachable!("functions have function types");
+                };
+
+                for ((name, _), ty) in args.iter().zip(params) {
+                    self.locals.insert(*name, ty);
+                }
+
+                self.this = match (this, owner) {
+                    (Some(refs), Some(owner)) => Some(self.this_type(owner, *refs)),
+                    (Some(_), None) => {
+                        self.at(value.span).types_this_outside_method();
+                        None
+                    }
+
+                    (None, _) => None,
+                };
+
+                self.returns = Some(ret);
+
+                let body = body.as_ref().map(|body| self.check_block(body));
+                let args = args.iter().map(|(name, _)| *name).collect();
+
+                hir::ValueNode::Function {
+                    this: *this,
+                    args,
+                    body,
+                }
+            }
+
+            rst::ValueNode::Variable { body, .. } => {
+                let body = body.as_ref().map(|body| self.check(body, signature.ty));
+                hir::ValueNode::Variable { body }
+            }
+        };
+
+        Some(hir::Value {
+            name,
+            node,
+            span: value.span,
+            anno: signature.ty,
+        })
+    }
+
+    /// Check that `value`, declared in `class` as `parent.part`, overrides a
+    /// member of a parent of `class` with a compatible signature.
+    fn check_override(&self, class: Name, value: &rst::Value, parent: Name, part: NamePart) {
+        let this = Type::new(self.db, TypeNode::Name(class));
+        let parent_type = Type::new(self.db, TypeNode::Name(parent));
+
+        let inherits = parent != class
+            && self.classes.contains_key(&parent)
+            && self
+                .info
+                .subtypes(self.db)
+                .is_class_subtype(&this, &parent_type);
+
+        if !inherits {
+            self.at(value.span)
+                .types_override_not_inherited(parent_type);
+            return;
+        }
+
+        let Some(overridden) = self.members.lookup(self.db, parent, part) else {
+            self.at(value.span)
+                .types_override_no_member(parent_type, part);
+            return;
+        };
+
+        self.check_visible(value.span, overridden.name);
+        self.check_override_signature(class, value, overridden);
+    }
+
+    /// Check that `value`, declared in `class` with the same name as a member
+    /// it inherits, is compatible with that member. Private members of the
+    /// parents aren't overridden, only hidden.
+    fn check_implicit_override(&self, class: Name, value: &rst::Value, part: NamePart) {
+        let Some(linearization) = self.members.linearizations(self.db).get(&class) else {
+            return;
+        };
+
+        let overridden = linearization
+            .iter()
+            .skip(1)
+            .find_map(|parent| self.members.lookup(self.db, *parent, part))
+            .filter(|member| self.names.is_visible(self.db, member.name, class));
+
+        if let Some(overridden) = overridden {
+            self.check_override_signature(class, value, overridden);
+        }
+    }
+
+    /// Check that the signature of `value` in `class` is compatible with the
+    /// member it overrides.
+    fn check_override_signature(&self, class: Name, value: &rst::Value, overridden: Member) {
+        let Some(name) = value.name.declared(self.db, NamePrefix::Item(class)) else {
+            return;
+        };
+
+        let expected = &self.values[&overridden.name];
+        let found = &self.values[&name];
+
+        if expected.this != found.this {
+            self.at(value.span)
+                .types_override_this_mismatch(expected.span, expected.this);
+            return;
+        }
+
+        // Generic functions are compared in terms of the type parameters of
+        // the overridden function.
+        let params: Vec<_> = expected
+            .type_params
+            .iter()
+            .map(|param| Type::new(self.db, TypeNode::Name(*param)))
+            .collect();
+
+        let found_type = if found.type_params.len() == params.len() {
+            Substitution::new(&found.type_params, &params).apply(self.db, &found.ty)
+        } else {
+            found.ty
+        };
+
+        let expected_type = self.member_type(self.this_type(class, 0), overridden.name);
+        let compatible = found.type_params.len() == params.len()
+            && if expected.variable || found.variable {
+                expected.variable == found.variable && expected_type == found_type
+            } else {
+                self.conforms(found_type, expected_type)
+            };
+
+        if !compatible {
+            self.at(value.span)
+                .types_override_mismatch(expected.span, expected_type, found.ty);
+        }
+    }
+
+    /// Check that a class which declares no abstract members of its own
+    /// implements every abstract member it inherits.
+    fn check_implemented(&self, name: Name, class: &rst::Class) {
+        let declares_abstract = class.fields.values.iter().any(|value| {
+            matches!(
+                value.node,
+                rst::ValueNode::Function {
+                    body: None,
+                    runtime: false,
+                    ..
+                }
+            )
+        });
+
+        if class.kind != ClassKind::Class || declares_abstract {
+            return;
+        }
+
+        let missing = self.abstract_members(name);
+        if !missing.is_empty() {
+            let class_type = Type::new(self.db, TypeNode::Name(name));
+            self.at(class.span)
+                .types_missing_implementations(class_type, missing);
+        }
+    }
+
+    /// The members of `class` without an implementation, along with where they
+    /// were declared.
+    fn abstract_members(&self, class: Name) -> Vec<(NamePart, Span)> {
+        let mut members: Vec<_> = self
+            .members
+            .abstract_members(self.db, class)
+            .into_iter()
+            .map(|member| (member.name.name(self.db), self.values[&member.name].span))
+            .collect();
+
+        members.sort_by_key(|(_, span)| span.start);
+        members
+    }
+
+    /// The scope the members of `owner` are declared in, or the scope of
+    /// top-level items if there is no owner.
+    fn scope(&self, owner: Option<Name>) -> NamePrefix {
+        match owner {
+            Some(owner) => NamePrefix::Item(owner),
+            None => NamePrefix::Source(self.source),
+        }
+    }
+
+    /// The type of `this` in a method of `class` taking it behind `refs`
+    /// references.
+    fn this_type(&self, class: Name, refs: usize) -> Type {
+        let args: Vec<_> = self
+            .info
+            .subtypes(self.db)
+            .params(&class)
+            .iter()
+            .map(|(param, _)| Type::new(self.db, TypeNode::Name(*param)))
+            .collect();
+
+        (0..refs).fold(instance(self.db, class, &args), |ty, _| {
+            Type::new(self.db, TypeNode::Reference(ty))
+        })
+    }
+
+    /// The class of string literals, which is whatever `String` refers to at
+    /// the top level. Usually, that is the one declared in the prelude.
+    fn string_type(&self, span: Span) -> Type {
+        self.builtin_class(span, "String")
+    }
+
+    /// The class every interpolated expression must be a subtype of.
+    fn stringable_type(&self, span: Span) -> Type {
+        self.builtin_class(span, "Stringable")
+    }
+
+    /// The class the language itself relies on which `name` refers to at the
+    /// top level.
+    fn builtin_class(&self, span: Span, name: &str) -> Type {
+        let part = NamePart::new(self.db, NameNode::Type(name.into()));
+        let found = lookup_top_level(self.db, self.source, part)
+            .first()
+            .copied();
+
+        if let Some(found) = found.filter(|found| self.classes.contains_key(found)) {
+            Type::new(self.db, TypeNode::Name(found))
+        } else {
+            self.at(span).types_missing_builtin(name);
+            self.bottom()
+        }
+    }
+
+    fn lower(&self, ty: &rst::Type) -> Type {
+        self.lowerer().lower(ty)
+    }
+
+    fn lowerer(&self) -> TypeLowerer<'a> {
+        let lowerer = TypeLowerer::new(
+            self.db,
+            self.source,
+            self.classes,
+            self.info.nested(self.db),
+        );
+        match self.item {
+            Some(item) => lowerer.within(self.names, item),
+            None => lowerer,
+        }
+    }
+
+    /// Report an error if `name` is a private member which is not visible from
+    /// the item being checked.
+    fn check_visible(&self, span: Span, name: Name) {
+        let Some(item) = self.item else {
+            return;
+        };
+
+        if !self.names.is_visible(self.db, name, item) {
+            let declared = self.names.spans(self.db)[&name];
+            self.at(span)
+                .once(&self.private)
+                .resolve_private_name(name.name(self.db), declared);
+        }
+    }
+
+    /// Is a value of type `found` usable where `expected` is? Errors are
+    /// represented by `!`, which is compatible with anything so as to avoid
+    /// cascading errors.
+    fn conforms(&self, found: Type, expected: Type) -> bool {
+        expected.node(self.db) == TypeNode::Bottom
+            || self
+                .info
+                .subtypes(self.db)
+                .is_subtype(self.db, &found, &expected)
+    }
+
+    /// Report an error if `found` does not conform to `expected`.
+    fn subsume(&self, span: Span, found: Type, expected: Type) {
+        if !self.conforms(found, expected) {
+            self.at(span).types_mismatched(expected, found);
+        }
+    }
+
+    fn bottom(&self) -> Type {
+        Type::new(self.db, TypeNode::Bottom)
+    }
+
+    fn unit(&self) -> Type {
+        Type::new(self.db, TypeNode::Unit)
+    }
+
+    fn at(&self, span: Span) -> MessageMaker<'a> {
+        MessageMaker::at(self.db, span)
+    }
 }
@@ -0,0 +1,190 @@
+use super::Checker;
+use crate::hir;
+use crate::rst;
+use crate::source::Span;
+use crate::types::{Type, TypeNode};
+
+impl Checker<'_> {
+    pub(super) fn check_block(&mut self, block: &rst::Block) -> hir::Block {
+        for (name, ty) in block.declarations.iter() {
+            let ty = self.lower(ty);
+            self.locals.insert(*name, ty);
+        }
+
+        let statements = block
+            .statements
+            .iter()
+            .map(|statement| self.check_statement(statement))
+            .collect();
+
+        hir::Block {
+            declared: block.declarations.iter().map(|(name, _)| *name).collect(),
+            statements,
+        }
+    }
+
+    fn check_statement(&mut self, statement: &rst::Statement) -> hir::Statement {
+        let node = match &statement.node {
+            rst::StatementNode::Expression(expr) => {
+                hir::StatementNode::Expression(self.infer(expr))
+            }
+
+            rst::StatementNode::Assignment(target, body) => {
+                let target = self.check_place(target);
+                let body = self.check(body, target.anno);
+                hir::StatementNode::Assignment(target, body)
+            }
+
+            rst::StatementNode::Return(expr) => {
+                let expected = self.returns.unwrap_or_else(|| self.unit());
+                hir::StatementNode::Return(self.check(expr, expected))
+            }
+
+            rst::StatementNode::Case(scrutinee, arms) => {
+                let scrutinee = self.infer(scrutinee);
+                let arms = arms
+                    .iter()
+                    .map(|arm| hir::CaseArm {
+                        pattern: self.check_pattern(&arm.pattern, scrutinee.anno),
+                        body: self.check_block(&arm.body),
+                        span: arm.span,
+                    })
+                    .collect::<Vec<_>>();
+
+                let exhaustive = self.check_exhaustiveness(statement.span, scrutinee.anno, &arms);
+                hir::StatementNode::Case(scrutinee, arms, exhaustive)
+            }
+
+            rst::StatementNode::If(cond, then, otherwise) => {
+                let cond = self.check(cond, Type::new(self.db, TypeNode::Boolean));
+                let then = self.check_block(then);
+                let otherwise = otherwise.as_ref().map(|block| self.check_block(block));
+                hir::StatementNode::If(cond, then, otherwise)
+            }
+
+            rst::StatementNode::While(cond, body) => {
+                let cond = self.check(cond, Type::new(self.db, TypeNode::Boolean));
+                let body = self.check_block(body);
+                hir::StatementNode::While(cond, body)
+            }
+
+            rst::StatementNode::Loop(body) => hir::StatementNode::Loop(self.check_block(body)),
+
+            rst::StatementNode::Break => hir::StatementNode::Break,
+            rst::StatementNode::Continue => hir::StatementNode::Continue,
+            rst::StatementNode::Null => hir::StatementNode::Null,
+        };
+
+        hir::Statement {
+            node,
+            span: statement.span,
+        }
+    }
+
+    /// Check the target of an assignment, which must be a variable or a field.
+    fn check_place(&mut self, target: &rst::Expression) -> hir::Expression {
+        let assignable = match &target.node {
+            rst::ExpressionNode::Name(name) => {
+                self.locals.contains_key(name)
+                    || self.values.get(name).is_some_and(|value| value.variable)
+            }
+
+            rst::ExpressionNode::Field(..) => true,
+            rst::ExpressionNode::Invalid => true,
+            _ => false,
+        };
+
+        let target = self.infer(target);
+
+        let assignable = assignable
+            && match &target.node {
+                hir::ExpressionNode::Field(_, member) => self.values[member].variable,
+                _ => true,
+            };
+
+        if !assignable {
+            self.at(target.span).types_invalid_assignment();
+        }
+
+        target
+    }
+
+    fn check_pattern(&mut self, pattern: &rst::Pattern, expected: Type) -> hir::Pattern {
+        let (node, anno) = match &pattern.node {
+            rst::PatternNode::Binding(name) => {
+                self.locals.insert(*name, expected);
+                (hir::PatternNode::Binding(*name), expected)
+            }
+
+            rst::PatternNode::Class(class, fields) => {
+                let ty = self.lowerer().lower_class(class);
+                let (TypeNode::Name(class) | TypeNode::Applied(class, _)) = ty.node(self.db) else {
+                    // Still give any bindings within the pattern a type
+                    for field in fields {
+                        let _ = self.check_pattern(field, self.bottom());
+                    }
+
+                    return hir::Pattern {
+                        node: hir::PatternNode::Invalid,
+                        span: pattern.span,
+                        anno: self.bottom(),
+                    };
+                };
+
+                if !self.narrows(ty, expected) {
+                    self.at(class_span(pattern)).types_mismatched(expected, ty);
+                }
+
+                let types = self.field_types(ty, Some(expected));
+                if types.len() != fields.len() {
+                    self.at(pattern.span)
+                        .types_wrong_argument_count(types.len(), fields.len());
+                }
+
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| {
+                        let ty = types
+                            .get(index)
+                            .map(|(_, ty)| *ty)
+                            .unwrap_or_else(|| self.bottom());
+                        self.check_pattern(field, ty)
+                    })
+                    .collect();
+
+                (hir::PatternNode::Class(class, fields), ty)
+            }
+
+            rst::PatternNode::Invalid => (hir::PatternNode::Invalid, self.bottom()),
+        };
+
+        hir::Pattern {
+            node,
+            span: pattern.span,
+            anno,
+        }
+    }
+
+    /// Can a value of type `expected` be an instance of the class `ty`?
+    fn narrows(&self, ty: Type, expected: Type) -> bool {
+        let head = |ty: Type| match ty.node(self.db) {
+            TypeNode::Applied(name, _) => Type::new(self.db, TypeNode::Name(name)),
+            _ => ty,
+        };
+
+        let expected = self.deref(expected);
+        let subtypes = self.info.subtypes(self.db);
+
+        expected.node(self.db) == TypeNode::Bottom
+            || subtypes.is_class_subtype(&head(ty), &head(expected))
+    }
+}
+
+/// The span of the class name of a class pattern.
+fn class_span(pattern: &rst::Pattern) -> Span {
+    match &pattern.node {
+        rst::PatternNode::Class(class, _) => class.span,
+        _ => pattern.span,
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::names::Name;
+use crate::Db;
+
+use super::{Type, TypeInfo, TypeNode};
+
+/// A mapping from the type parameters of a generic item to the types they are
+/// instantiated with.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Substitution {
+    map: HashMap<Name, Type>,
+}
+
+impl Substitution {
+    pub fn new(params: &[Name], args: &[Type]) -> Self {
+        debug_assert_eq!(params.len(), args.len());
+
+        let map = params.iter().copied().zip(args.iter().copied()).collect();
+        Self { map }
+    }
+
+    /// Replace every type parameter in `ty` with the type it is mapped to.
+    pub fn apply(&self, db: &dyn Db, ty: &Type) -> Type {
+        if self.map.is_empty() {
+            return *ty;
+        }
+
+        let node = match ty.node(db) {
+            TypeNode::Name(name) => match self.map.get(&name) {
+                Some(ty) => return *ty,
+                None => TypeNode::Name(name),
+            },
+
+            TypeNode::Applied(name, args) => {
+                let args = args.iter().map(|ty| self.apply(db, ty)).collect();
+                TypeNode::Applied(name, args)
+            }
+
+            TypeNode::Function(from, to) => {
+                let from = from.iter().map(|ty| self.apply(db, ty)).collect();
+                let to = self.apply(db, &to);
+                TypeNode::Function(from, to)
+            }
+
+            TypeNode::Reference(of) => TypeNode::Reference(self.apply(db, &of)),
+
+            node @ (TypeNode::Bottom
+            | TypeNode::Unit
+            | TypeNode::Int
+            | TypeNode::Nat
+            | TypeNode::Boolean) => node,
+        };
+
+        Type::new(db, node)
+    }
+}
+
+/// The type of instances of `class` applied to `args`, which should be empty if
+/// the class is not generic.
+pub fn instance(db: &dyn Db, class: Name, args: &[Type]) -> Type {
+    if args.is_empty() {
+        Type::new(db, TypeNode::Name(class))
+    } else {
+        Type::new(db, TypeNode::Applied(class, args.to_vec()))
+    }
+}
+
+impl TypeInfo {
+    /// The substitution for the type parameters of `class` when seen through a
+    /// value of type `of`, which must be (a subtype of) an instance of
+    /// `class`. For instance, if `class IntList inherits List(Int)`, then
+    /// looking at `List` through `IntList` maps its parameter to `Int`.
+    pub fn substitution(&self, db: &dyn Db, of: &Type, class: Name) -> Option<Substitution> {
+        let subtypes = self.subtypes(db);
+        let target = Type::new(db, TypeNode::Name(class));
+
+        // The first type of the path is the instance of `class` itself.
+        let path = subtypes.supertype_path(db, of, &target)?;
+        let args = match path.first()?.node(db) {
+            TypeNode::Applied(_, args) => args,
+            _ => Vec::new(),
+        };
+
+        let params: Vec<_> = subtypes
+            .params(&class)
+            .iter()
+            .map(|(param, _)| *param)
+            .collect();
+
+        (params.len() == args.len()).then(|| Substitution::new(&params, &args))
+    }
+
+    /// The type of a member declared with type `member` in `class`, when
+    /// accessed through a value of type `of`.
+    pub fn member_type(&self, db: &dyn Db, of: &Type, class: Name, member: &Type) -> Option<Type> {
+        self.substitution(db, of, class)
+            .map(|subst| subst.apply(db, member))
+    }
+}
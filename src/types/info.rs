@@ -3,12 +3,15 @@ use std::collections::{HashMap, HashSet};
 use crate::inheritance::inherit_components;
 use crate::messages::MessageMaker;
 use crate::names::{Name, NamePart};
-use crate::resolution::resolve_names;
+use crate::resolution::{visible_classes, visible_names, NamesWithin};
 use crate::rst::{self, Class, ClassKind, DeclarationName};
 use crate::source::{Source, Span};
 use crate::Db;
 
+use super::generics::instance;
+use super::lower::TypeLowerer;
 use super::subtyping::Subtypes;
+use super::variance::VarianceChecker;
 use super::{Type, TypeNode};
 
 #[salsa::tracked]
@@ -25,10 +28,12 @@ pub struct TypeInfo {
 
 #[salsa::tracked]
 pub fn type_info(db: &dyn Db, source: Source) -> TypeInfo {
-    let classes = resolve_names(db, source).tree(db).classes(db);
+    let classes = visible_classes(db, source);
     let components = inherit_components(db, source);
 
-    let mut collector = InfoCollector::new(db, classes);
+    let names = visible_names(db, source);
+
+    let mut collector = InfoCollector::new(db, source, classes, names);
     for component in components {
         collector.collect_component(component);
     }
@@ -38,20 +43,44 @@ pub fn type_info(db: &dyn Db, source: Source) -> TypeInfo {
 
 struct InfoCollector<'a> {
     db: &'a dyn Db,
+
+    /// The source the information is collected for. The classes of imported
+    /// modules are included, but only checked along with their own source.
+    source: Source,
+
     classes: &'a HashMap<Name, Class>,
+    names: NamesWithin,
     subtypes: Subtypes,
     open: HashSet<Type>,
     nested: HashMap<Name, HashMap<NamePart, Name>>,
+
+    /// The variant every class or variant nested directly within one is
+    /// implicitly a subtype of.
+    variants: HashMap<Name, Name>,
 }
 
 impl<'a> InfoCollector<'a> {
-    pub fn new(db: &'a dyn Db, classes: &'a HashMap<Name, Class>) -> Self {
+    pub fn new(
+        db: &'a dyn Db,
+        source: Source,
+        classes: &'a HashMap<Name, Class>,
+        names: NamesWithin,
+    ) -> Self {
+        let variants = classes
+            .iter()
+            .filter(|(_, class)| class.kind == ClassKind::Variant)
+            .flat_map(|(name, class)| class.fields.classes.iter().map(|nested| (*nested, *name)))
+            .collect();
+
         Self {
             db,
+            source,
             classes,
+            names,
             subtypes: Subtypes::new(),
             open: HashSet::new(),
             nested: HashMap::new(),
+            variants,
         }
     }
 
@@ -75,6 +104,13 @@ impl<'a> InfoCollector<'a> {
             self.open.insert(class);
         }
 
+        let params = class
+            .type_params
+            .iter()
+            .map(|param| (param.name, param.variance))
+            .collect();
+        self.subtypes.add_params(name, params);
+
         let mut nested = HashMap::new();
         for global in class.fields.classes.iter() {
             let class = self
@@ -99,67 +135,93 @@ impl<'a> InfoCollector<'a> {
     fn declare_subtyping(&mut self, name: Name) {
         let class = self.classes.get(&name).expect("not a class name!");
 
-        if let ClassKind::Variant = class.kind {
-            // implicit subtyping galore
-            todo!()
+        if class.span.source == self.source {
+            let variant = self
+                .variants
+                .get(&name)
+                .map(|variant| &self.classes[variant]);
+            VarianceChecker::check_class(self.db, self.classes, class, variant);
+        }
+
+        let mut inherits = Vec::new();
+        for ty in class.inherits.iter() {
+            let inherit = self.to_type(name, ty);
+
+            // Only the classes nested within a variant are its cases, so that
+            // case analysis knows every one of them.
+            match self.closed_variant(inherit) {
+                Some(variant) if !self.is_nested_within(name, variant) => {
+                    let variant = Type::new(self.db, TypeNode::Name(variant));
+                    self.at(ty.span).types_closed_variant(variant);
+                }
+
+                _ => inherits.push(inherit),
+            }
         }
 
+        // Anything nested within a variant is one of its cases, which are seen
+        // in terms of the type parameters of the variant.
+        if let Some(variant) = self.variants.get(&name) {
+            let params: Vec<_> = self
+                .subtypes
+                .params(variant)
+                .iter()
+                .map(|(param, _)| Type::new(self.db, TypeNode::Name(*param)))
+                .collect();
+
+            inherits.push(instance(self.db, *variant, &params));
+        }
+
+        // Cycles are only a matter of the classes, regardless of what they are
+        // applied to.
         let this_type = Type::new(self.db, TypeNode::Name(name));
-        for inherit in class.inherits.iter() {
-            let inherit = self.to_type(inherit);
+        for inherit in inherits {
+            let head = match inherit.node(self.db) {
+                TypeNode::Applied(head, _) => Type::new(self.db, TypeNode::Name(head)),
+                _ => inherit,
+            };
 
-            if let Some(path) = self.subtypes.supertype_path(&this_type, &inherit) {
+            if let Some(path) = self.subtypes.class_path(&this_type, &head) {
                 self.at(class.span).types_subtype_cycle(Some(path));
             } else {
-                self.subtypes.add_subtype(inherit, this_type);
+                self.subtypes.add_inherit(self.db, name, inherit);
             }
         }
     }
 
-    fn to_type(&self, ty: &rst::Type) -> Type {
-        let node = match &ty.node {
-            rst::TypeNode::Name(name) => TypeNode::Name(*name),
-            rst::TypeNode::Field(of, field) => {
-                let of = self.to_type(of);
-                if let TypeNode::Name(name) = of.node(self.db) {
-                    self.nested
-                        .get(&name)
-                        .expect("todo: ordering issues")
-                        .get(field)
-                        .map(|name| TypeNode::Name(*name))
-                        .unwrap_or_else(|| {
-                            self.at(ty.span).resolve_unresolved_name();
-                            TypeNode::Bottom
-                        })
-                } else {
-                    todo!("hmmmm");
-                }
-            }
+    /// The variant a type is an instance of, if it is one. Every class
+    /// declared so far is open, so anything else is a variant.
+    fn closed_variant(&self, ty: Type) -> Option<Name> {
+        let (TypeNode::Name(class) | TypeNode::Applied(class, _)) = ty.node(self.db) else {
+            return None;
+        };
 
-            rst::TypeNode::Applied(..) => todo!(),
+        let head = Type::new(self.db, TypeNode::Name(class));
+        (self.classes.contains_key(&class) && !self.open.contains(&head)).then_some(class)
+    }
 
-            rst::TypeNode::Function(from, to) => {
-                let from = from.iter().map(|ty| self.to_type(ty)).collect();
-                let to = self.to_type(to);
-                TypeNode::Function(from, to)
+    /// Is `class` nested within `variant`, directly or through other variants?
+    fn is_nested_within(&self, class: Name, variant: Name) -> bool {
+        let mut within = self.variants.get(&class);
+        while let Some(enclosing) = within {
+            if *enclosing == variant {
+                return true;
             }
 
-            rst::TypeNode::Reference(of) => {
-                let of = self.to_type(of);
-                TypeNode::Reference(of)
-            }
+            within = self.variants.get(enclosing);
+        }
 
-            rst::TypeNode::Int => TypeNode::Int,
-            rst::TypeNode::Nat => TypeNode::Nat,
-            rst::TypeNode::Boolean => TypeNode::Boolean,
-            rst::TypeNode::Unit => TypeNode::Unit,
-            rst::TypeNode::Invalid => TypeNode::Bottom,
-        };
+        false
+    }
 
-        Type::new(self.db, node)
+    /// Lower a type written in the declaration of `within`.
+    fn to_type(&self, within: Name, ty: &rst::Type) -> Type {
+        TypeLowerer::new(self.db, self.source, self.classes, &self.nested)
+            .within(self.names, within)
+            .lower(ty)
     }
 
     fn at(&self, span: Span) -> MessageMaker<'a> {
-        MessageMaker::at(self.db, span)
+        MessageMaker::at(self.db, span).within(self.source)
     }
 }
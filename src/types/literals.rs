@@ -0,0 +1,58 @@
+use crate::ast::{NumberLiteral, NumberSuffix, NumberValue};
+use crate::messages::MessageMaker;
+use crate::source::Span;
+use crate::Db;
+
+use super::{Type, TypeNode};
+
+/// The type a number literal has because of its suffix, if any.
+pub fn suffix_type(db: &dyn Db, literal: &NumberLiteral) -> Option<Type> {
+    let node = match literal.suffix? {
+        NumberSuffix::Int => TypeNode::Int,
+        NumberSuffix::Nat => TypeNode::Nat,
+    };
+
+    Some(Type::new(db, node))
+}
+
+/// The smallest and largest values of the builtin integer types. `Int` is a
+/// signed and `Nat` an unsigned 64-bit integer.
+pub fn integer_range(ty: &TypeNode) -> Option<(i128, i128)> {
+    match ty {
+        TypeNode::Int => Some((i64::MIN.into(), i64::MAX.into())),
+        TypeNode::Nat => Some((0, u64::MAX.into())),
+        _ => None,
+    }
+}
+
+/// Check that a number literal fits within the integer type `ty`, reporting an
+/// error at `span` if it doesn't. `negated` is true if the literal is the
+/// operand of a negation, like `-5`.
+pub fn check_number_literal(
+    db: &dyn Db,
+    span: Span,
+    literal: &NumberLiteral,
+    negated: bool,
+    ty: Type,
+) -> bool {
+    let Some((min, max)) = integer_range(&ty.node(db)) else {
+        return false;
+    };
+
+    let value = match literal.value {
+        NumberValue::Integer(value) => i128::try_from(value).ok(),
+        NumberValue::Fraction { .. } => {
+            MessageMaker::at(db, span).types_fractional_literal(ty);
+            return false;
+        }
+    };
+
+    let value = value.map(|value| if negated { -value } else { value });
+    match value {
+        Some(value) if (min..=max).contains(&value) => true,
+        _ => {
+            MessageMaker::at(db, span).types_literal_out_of_range(ty, min, max);
+            false
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::messages::MessageMaker;
+use crate::names::{Name, NamePart};
+use crate::resolution::NamesWithin;
+use crate::rst::{self, Class};
+use crate::source::{Source, Span};
+use crate::Db;
+
+use super::{Type, TypeNode};
+
+/// Turns the types written in the source into [`Type`]s, checking that generic
+/// classes are given the right number of arguments.
+pub struct TypeLowerer<'a> {
+    db: &'a dyn Db,
+
+    /// The source the types are lowered for. Types written in imported modules
+    /// are lowered too, but problems with them are not reported here.
+    source: Source,
+    classes: &'a HashMap<Name, Class>,
+    nested: &'a HashMap<Name, HashMap<NamePart, Name>>,
+
+    /// The item the types are written in, if nested classes should only be
+    /// reachable if they are visible from it.
+    within: Option<(NamesWithin, Name)>,
+}
+
+impl<'a> TypeLowerer<'a> {
+    pub fn new(
+        db: &'a dyn Db,
+        source: Source,
+        classes: &'a HashMap<Name, Class>,
+        nested: &'a HashMap<Name, HashMap<NamePart, Name>>,
+    ) -> Self {
+        Self {
+            db,
+            source,
+            classes,
+            nested,
+            within: None,
+        }
+    }
+
+    /// Report private nested classes which are not visible from `item`.
+    pub fn within(self, names: NamesWithin, item: Name) -> Self {
+        Self {
+            within: Some((names, item)),
+            ..self
+        }
+    }
+
+    pub fn lower(&self, ty: &rst::Type) -> Type {
+        let node = match &ty.node {
+            rst::TypeNode::Name(name) => self.bare(ty.span, *name),
+
+            rst::TypeNode::Field(..) => match self.path(ty) {
+                Some(name) => self.bare(ty.span, name),
+                None => TypeNode::Bottom,
+            },
+
+            rst::TypeNode::Applied(to, args) => {
+                let args: Vec<_> = args.iter().map(|ty| self.lower(ty)).collect();
+                let name = self.path(to);
+
+                match name {
+                    Some(name) if self.arity(&name) == args.len() => TypeNode::Applied(name, args),
+                    Some(name) => {
+                        self.at(ty.span)
+                            .types_wrong_type_arity(self.arity(&name), args.len());
+                        TypeNode::Bottom
+                    }
+
+                    None => {
+                        self.at(ty.span).types_wrong_type_arity(0, args.len());
+                        TypeNode::Bottom
+                    }
+                }
+            }
+
+            rst::TypeNode::Function(from, to) => {
+                let from = from.iter().map(|ty| self.lower(ty)).collect();
+                let to = self.lower(to);
+                TypeNode::Function(from, to)
+            }
+
+            rst::TypeNode::Reference(of) => {
+                let of = self.lower(of);
+                TypeNode::Reference(of)
+            }
+
+            rst::TypeNode::Int => TypeNode::Int,
+            rst::TypeNode::Nat => TypeNode::Nat,
+            rst::TypeNode::Boolean => TypeNode::Boolean,
+            rst::TypeNode::Unit => TypeNode::Unit,
+            rst::TypeNode::Invalid => TypeNode::Bottom,
+        };
+
+        Type::new(self.db, node)
+    }
+
+    /// Lower the class named by a pattern, which may be generic without being
+    /// given any arguments.
+    pub fn lower_class(&self, ty: &rst::Type) -> Type {
+        match self.path(ty) {
+            Some(name) => Type::new(self.db, TypeNode::Name(name)),
+            None => Type::new(self.db, TypeNode::Bottom),
+        }
+    }
+
+    /// The class named by a (possibly nested) path like `A.B.C`, regardless of
+    /// how many type parameters any of them have.
+    fn path(&self, ty: &rst::Type) -> Option<Name> {
+        match &ty.node {
+            rst::TypeNode::Name(name) => Some(*name),
+            rst::TypeNode::Field(of, field) => {
+                let of = self.path(of)?;
+                let nested = self.nested.get(&of).and_then(|nested| nested.get(field));
+                match (nested, self.within) {
+                    (None, _) => self.at(ty.span).resolve_unresolved_name(),
+                    (Some(nested), Some((names, item)))
+                        if !names.is_visible(self.db, *nested, item) =>
+                    {
+                        self.at(ty.span)
+                            .resolve_private_name(*field, names.spans(self.db)[nested]);
+                    }
+
+                    _ => {}
+                }
+
+                nested.copied()
+            }
+
+            rst::TypeNode::Invalid => None,
+            _ => {
+                let _ = self.lower(ty);
+                None
+            }
+        }
+    }
+
+    /// A class mentioned without any type arguments.
+    fn bare(&self, span: Span, name: Name) -> TypeNode {
+        let arity = self.arity(&name);
+        if arity == 0 {
+            TypeNode::Name(name)
+        } else {
+            self.at(span).types_wrong_type_arity(arity, 0);
+            TypeNode::Bottom
+        }
+    }
+
+    /// The number of type parameters of the given class, or zero if the name
+    /// does not refer to a class.
+    fn arity(&self, name: &Name) -> usize {
+        self.classes
+            .get(name)
+            .map(|class| class.type_params.len())
+            .unwrap_or(0)
+    }
+
+    fn at(&self, span: Span) -> MessageMaker<'a> {
+        MessageMaker::at(self.db, span).within(self.source)
+    }
+}
@@ -0,0 +1,306 @@
+//! The members of every class, including those it inherits.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::messages::MessageMaker;
+use crate::names::{Name, NamePart, NamePrefix};
+use crate::resolution::{visible_classes, visible_names};
+use crate::rst::{Class, ValueNode};
+use crate::source::{Source, Span};
+use crate::Db;
+
+use super::{type_info, Subtypes, Type, TypeNode};
+
+/// A member of a class, either declared in it or inherited from a supertype.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Member {
+    /// The declaration of the member.
+    pub name: Name,
+
+    /// The class which declares the member.
+    pub owner: Name,
+
+    /// Can the member be accessed from outside of the class which declares it?
+    pub public: bool,
+
+    /// Does the member have a definition? Functions without a body are
+    /// abstract, and must be overridden before the class can be instantiated,
+    /// unless they are provided by the runtime.
+    pub implemented: bool,
+}
+
+#[salsa::tracked]
+pub struct MemberTable {
+    #[return_ref]
+    pub members: HashMap<Name, HashMap<NamePart, Member>>,
+
+    /// The C3 linearization of every class, which starts with the class itself
+    /// and orders every supertype before its own supertypes.
+    #[return_ref]
+    pub linearizations: HashMap<Name, Vec<Name>>,
+}
+
+impl MemberTable {
+    /// Find the member of `class` with the given name.
+    pub fn lookup(&self, db: &dyn Db, class: Name, name: NamePart) -> Option<Member> {
+        self.members(db)
+            .get(&class)
+            .and_then(|members| members.get(&name))
+            .copied()
+    }
+
+    /// The members of `class` which have not been implemented.
+    pub fn abstract_members(&self, db: &dyn Db, class: Name) -> Vec<Member> {
+        self.members(db)
+            .get(&class)
+            .into_iter()
+            .flat_map(|members| members.values())
+            .filter(|member| !member.implemented)
+            .copied()
+            .collect()
+    }
+}
+
+#[salsa::tracked]
+pub fn member_table(db: &dyn Db, source: Source) -> MemberTable {
+    let classes = visible_classes(db, source);
+    let subtypes = type_info(db, source).subtypes(db);
+    let public = visible_names(db, source).public(db);
+
+    let mut collector = MemberCollector {
+        db,
+        source,
+        classes,
+        subtypes,
+        public,
+        members: HashMap::new(),
+        linearizations: HashMap::new(),
+        inconsistent: HashSet::new(),
+    };
+
+    let mut names: Vec<_> = classes
+        .iter()
+        .map(|(name, class)| (class.span, *name))
+        .collect();
+    names.sort_by_key(|(span, _)| (span.source, span.start));
+
+    for (_, class) in names {
+        collector.collect(class);
+    }
+
+    MemberTable::new(db, collector.members, collector.linearizations)
+}
+
+struct MemberCollector<'a> {
+    db: &'a dyn Db,
+
+    /// The source the members are collected for. Problems with the classes of
+    /// imported modules are reported along with their own source.
+    source: Source,
+
+    classes: &'a HashMap<Name, Class>,
+    subtypes: &'a Subtypes,
+    public: &'a HashSet<Name>,
+    members: HashMap<Name, HashMap<NamePart, Member>>,
+    linearizations: HashMap<Name, Vec<Name>>,
+
+    /// The classes without a consistent linearization.
+    inconsistent: HashSet<Name>,
+}
+
+impl MemberCollector<'_> {
+    /// Collect the members of `class`. Members declared in the class itself
+    /// override any inherited members of the same name. Of the rest, those
+    /// which aren't overridden by another inherited member are inherited, and
+    /// it is an error for there to be more than one such member.
+    fn collect(&mut self, class: Name) -> &HashMap<NamePart, Member> {
+        if !self.members.contains_key(&class) {
+            // Guard against cycles, which have already been reported
+            self.members.insert(class, HashMap::new());
+
+            let parents = self.parents(class);
+            let mut inherited: Vec<(NamePart, Vec<Member>)> = Vec::new();
+
+            for parent in parents.iter() {
+                let mut members: Vec<_> = self.collect(*parent).clone().into_iter().collect();
+                members.sort_by_key(|(_, member)| self.span(member).start);
+
+                for (name, member) in members {
+                    match inherited.iter_mut().find(|(other, _)| *other == name) {
+                        Some((_, candidates)) if candidates.contains(&member) => {}
+                        Some((_, candidates)) => candidates.push(member),
+                        None => inherited.push((name, vec![member])),
+                    }
+                }
+            }
+
+            let linearization = self.linearize(class, &parents);
+            let mut members = self.declared(class);
+
+            for (name, candidates) in inherited {
+                if members.contains_key(&name) {
+                    continue;
+                }
+
+                let mut candidates: Vec<_> = candidates
+                    .iter()
+                    .filter(|member| !candidates.iter().any(|other| self.overrides(other, member)))
+                    .copied()
+                    .collect();
+
+                candidates.sort_by_key(|member| {
+                    linearization
+                        .iter()
+                        .position(|class| *class == member.owner)
+                });
+
+                if candidates.len() > 1 {
+                    let spans = candidates.iter().map(|member| self.span(member)).collect();
+                    let class_type = Type::new(self.db, TypeNode::Name(class));
+                    MessageMaker::at(self.db, self.classes[&class].span)
+                        .within(self.source)
+                        .types_ambiguous_member(class_type, name, spans);
+                }
+
+                if let Some(member) = candidates.first() {
+                    members.insert(name, *member);
+                }
+            }
+
+            self.linearizations.insert(class, linearization);
+            self.members.insert(class, members);
+        }
+
+        &self.members[&class]
+    }
+
+    /// Compute the C3 linearization of `class`, given its direct parents. If
+    /// there is no consistent linearization, an error is reported and the
+    /// supertypes are just listed depth first.
+    fn linearize(&mut self, class: Name, parents: &[Name]) -> Vec<Name> {
+        let mut lists: Vec<Vec<Name>> = parents
+            .iter()
+            .map(|parent| {
+                self.linearizations
+                    .get(parent)
+                    .cloned()
+                    .unwrap_or_else(|| vec![*parent])
+            })
+            .collect();
+        lists.push(parents.to_vec());
+
+        let mut result = vec![class];
+        loop {
+            lists.retain(|list| !list.is_empty());
+            if lists.is_empty() {
+                return result;
+            }
+
+            // The first head which doesn't appear later in any other list
+            let head = lists
+                .iter()
+                .map(|list| list[0])
+                .find(|head| lists.iter().all(|list| !list[1..].contains(head)));
+
+            let Some(head) = head else {
+                // Don't report the same problem again for every subclass
+                if !parents
+                    .iter()
+                    .any(|parent| self.inconsistent.contains(parent))
+                {
+                    let class_type = Type::new(self.db, TypeNode::Name(class));
+                    MessageMaker::at(self.db, self.classes[&class].span)
+                        .within(self.source)
+                        .types_inconsistent_hierarchy(class_type);
+                }
+
+                self.inconsistent.insert(class);
+
+                let mut result = vec![class];
+                for parent in parents {
+                    for ty in self.linearizations.get(parent).into_iter().flatten() {
+                        if !result.contains(ty) {
+                            result.push(*ty);
+                        }
+                    }
+                }
+
+                return result;
+            };
+
+            result.push(head);
+            for list in lists.iter_mut() {
+                if list[0] == head {
+                    list.remove(0);
+                }
+            }
+        }
+    }
+
+    /// The classes `class` directly inherits from, in order.
+    fn parents(&self, class: Name) -> Vec<Name> {
+        self.subtypes
+            .inherits(&class)
+            .iter()
+            .filter_map(|ty| match ty.node(self.db) {
+                TypeNode::Name(name) | TypeNode::Applied(name, _) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Does `member` take the place of `other` in classes which inherit both?
+    fn overrides(&self, member: &Member, other: &Member) -> bool {
+        let owner = Type::new(self.db, TypeNode::Name(member.owner));
+        let other_owner = Type::new(self.db, TypeNode::Name(other.owner));
+
+        member.owner != other.owner && self.subtypes.is_class_subtype(&owner, &other_owner)
+    }
+
+    /// Where the given member was declared.
+    fn span(&self, member: &Member) -> Span {
+        self.classes[&member.owner]
+            .fields
+            .values
+            .iter()
+            .find(|value| {
+                value.name.declared(self.db, NamePrefix::Item(member.owner)) == Some(member.name)
+            })
+            .map(|value| value.span)
+            .expect("members are declared in their owner")
+    }
+
+    /// The members declared directly within `class`. Overrides like
+    /// `function Parent.member` are named as if they were declared without
+    /// the prefix.
+    fn declared(&self, class: Name) -> HashMap<NamePart, Member> {
+        let Some(declaration) = self.classes.get(&class) else {
+            return HashMap::new();
+        };
+
+        declaration
+            .fields
+            .values
+            .iter()
+            .filter_map(|value| {
+                let name = value.name.declared(self.db, NamePrefix::Item(class))?;
+
+                let member = Member {
+                    name,
+                    owner: class,
+                    public: self.public.contains(&name),
+                    implemented: !matches!(
+                        value.node,
+                        ValueNode::Function {
+                            body: None,
+                            runtime: false,
+                            ..
+                        }
+                    ),
+                };
+
+                Some((name.name(self.db), member))
+            })
+            .collect()
+    }
+}
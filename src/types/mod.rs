@@ -1,10 +1,18 @@
 mod check;
+mod generics;
 mod info;
+mod literals;
+mod lower;
+mod members;
 mod subtyping;
+mod variance;
 
 pub use check::annotate;
+pub use generics::Substitution;
 pub use info::{type_info, TypeInfo};
 use itertools::Itertools;
+pub use literals::{check_number_literal, integer_range, suffix_type};
+pub use members::{member_table, Member, MemberTable};
 pub use subtyping::{SubtypeVisualizer, Subtypes};
 
 use crate::names::{Name, NameNode};
@@ -28,6 +36,9 @@ pub enum TypeNode {
     Boolean,
     Name(Name),
 
+    /// A generic class applied to some type arguments, like `List(Int)`.
+    Applied(Name, Vec<Type>),
+
     Function(Vec<Type>, Type),
     Reference(Type),
 }
@@ -43,8 +54,18 @@ pub fn pretty_type(db: &dyn Db, ty: &Type) -> String {
             NameNode::Invalid => "<error>".to_string(),
             NameNode::Type(ty) => ty.clone(),
             NameNode::Value(ty) => ty.clone(),
+            NameNode::Quoted(ty) => format!("\"{ty}\""),
         },
 
+        TypeNode::Applied(name, args) => {
+            let name = Type::new(db, TypeNode::Name(name));
+            format!(
+                "{}({})",
+                pretty_type(db, &name),
+                args.iter().map(|ty| pretty_type(db, ty)).join(", ")
+            )
+        }
+
         TypeNode::Function(from, to) => {
             format!(
                 "({}) {}",
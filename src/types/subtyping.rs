@@ -3,14 +3,24 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
+use crate::ast::Variance;
+use crate::names::Name;
 use crate::Db;
 
-use super::{pretty_type, Type};
+use super::generics::{instance, Substitution};
+use super::{pretty_type, Type, TypeNode};
 
 #[derive(Debug, Default, Eq, PartialEq)]
 pub struct Subtypes {
     supers: HashMap<Type, HashSet<Type>>,
     subs: HashMap<Type, HashSet<Type>>,
+
+    /// The type parameters of every class, along with their variance.
+    params: HashMap<Name, Vec<(Name, Variance)>>,
+
+    /// The declared supertypes of every class, in terms of its own type
+    /// parameters.
+    inherits: HashMap<Name, Vec<Type>>,
 }
 
 impl Subtypes {
@@ -18,6 +28,8 @@ impl Subtypes {
         Self {
             supers: HashMap::new(),
             subs: HashMap::new(),
+            params: HashMap::new(),
+            inherits: HashMap::new(),
         }
     }
 
@@ -30,33 +42,116 @@ impl Subtypes {
         self.assert_integrity();
     }
 
-    /// Returns true if `this` is a subtype of `of`, or if `this == of`. Linear
-    /// time complexity over the number of supertypes.
-    pub fn is_subtype(&self, this: &Type, of: &Type) -> bool {
-        if this == of {
-            return true;
-        }
+    /// Declare the type parameters of a class.
+    pub fn add_params(&mut self, class: Name, params: Vec<(Name, Variance)>) {
+        self.params.insert(class, params);
+    }
 
-        let Some(supers) = self.supers.get(this) else { return false; };
+    /// Make `class` inherit from `parent`, which may be an applied type whose
+    /// arguments mention the type parameters of `class`.
+    pub fn add_inherit(&mut self, db: &dyn Db, class: Name, parent: Type) {
+        let head = match parent.node(db) {
+            TypeNode::Applied(head, _) => Type::new(db, TypeNode::Name(head)),
+            _ => parent,
+        };
 
-        // Do a depth first search to see if this is a direct subtype of if any
-        // supertype is a parent.
-        supers.contains(of) || supers.iter().any(|parent| self.is_subtype(parent, of))
+        self.add_subtype(head, Type::new(db, TypeNode::Name(class)));
+        self.inherits.entry(class).or_default().push(parent);
+    }
+
+    /// The type parameters of the given class.
+    pub fn params(&self, class: &Name) -> &[(Name, Variance)] {
+        self.params.get(class).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The supertypes `class` directly inherits from, in the order they were
+    /// added and in terms of its own type parameters.
+    pub fn inherits(&self, class: &Name) -> &[Type] {
+        self.inherits.get(class).map(Vec::as_slice).unwrap_or(&[])
     }
 
-    pub fn supertype_path(&self, this: &Type, of: &Type) -> Option<Vec<Type>> {
+    /// Returns true if `this` is a subtype of `of`, or if `this == of`. The
+    /// arguments of applied types are compared according to the variance of
+    /// the corresponding type parameters, functions are contravariant in their
+    /// arguments and covariant in their return type, and references are
+    /// invariant. Linear time complexity over the number of supertypes.
+    pub fn is_subtype(&self, db: &dyn Db, this: &Type, of: &Type) -> bool {
+        match (this.node(db), of.node(db)) {
+            (TypeNode::Bottom, _) => true,
+
+            (TypeNode::Function(from, to), TypeNode::Function(of_from, of_to)) => {
+                from.len() == of_from.len()
+                    && from
+                        .iter()
+                        .zip(of_from.iter())
+                        .all(|(arg, of_arg)| self.is_subtype(db, of_arg, arg))
+                    && self.is_subtype(db, &to, &of_to)
+            }
+
+            (TypeNode::Reference(this), TypeNode::Reference(of)) => this == of,
+
+            _ => self.supertype_path(db, this, of).is_some(),
+        }
+    }
+
+    /// Find a chain of supertypes going from `this` up to `of`. The path is
+    /// ordered from `of` to `this`, and every type along it is instantiated
+    /// with the arguments `this` gives it. A generic class on its own is
+    /// treated as applied to its own type parameters, and a generic `of` on its
+    /// own matches any instance.
+    pub fn supertype_path(&self, db: &dyn Db, this: &Type, of: &Type) -> Option<Vec<Type>> {
         if this == of {
             return Some(vec![*this]);
         }
 
-        let supers = self.supers.get(this)?;
-        supers
-            .iter()
-            .find_map(|sup| self.supertype_path(sup, of))
-            .map(|mut path| {
-                path.push(*this);
-                path
-            })
+        let (head, mut args) = match this.node(db) {
+            TypeNode::Name(name) => (name, self.own_arguments(db, &name)),
+            TypeNode::Applied(name, args) => (name, args),
+            _ => return None,
+        };
+
+        let (target, expected) = match of.node(db) {
+            TypeNode::Name(name) => (name, None),
+            TypeNode::Applied(name, args) => (name, Some(args)),
+            _ => return None,
+        };
+
+        let classes = self.class_path(
+            &Type::new(db, TypeNode::Name(head)),
+            &Type::new(db, TypeNode::Name(target)),
+        )?;
+
+        // Carry the arguments up from `this`, one class at a time.
+        let mut current = head;
+        let mut path = vec![instance(db, head, &args)];
+        for parent in classes.iter().rev().skip(1) {
+            let TypeNode::Name(parent) = parent.node(db) else {
+                return None;
+            };
+
+            args = self.parent_arguments(db, current, &args, parent)?;
+            path.push(instance(db, parent, &args));
+            current = parent;
+        }
+
+        if let Some(expected) = expected {
+            let conforms = self
+                .params(&target)
+                .iter()
+                .zip(args.iter().zip(expected.iter()))
+                .all(|((_, variance), (arg, expected))| match variance {
+                    Variance::Invariant => arg == expected,
+                    Variance::Covariant => self.is_subtype(db, arg, expected),
+                    Variance::Contravariant => self.is_subtype(db, expected, arg),
+                });
+
+            if !conforms || args.len() != expected.len() {
+                return None;
+            }
+        }
+
+        path.reverse();
+        Some(path)
     }
 
     /// Returns an iterator over every supertype of the given type. The first
@@ -77,7 +172,81 @@ impl Subtypes {
     /// builds.
     pub fn assert_integrity(&self) {
         for (a, b) in self.supers.keys().tuple_combinations() {
-            assert!(!(self.is_subtype(a, b) && self.is_subtype(b, a)));
+            assert!(!(self.is_class_subtype(a, b) && self.is_class_subtype(b, a)));
+        }
+    }
+
+    /// Like [`Subtypes::is_subtype`], but only looking at the subtyping graph
+    /// of the classes themselves.
+    pub fn is_class_subtype(&self, this: &Type, of: &Type) -> bool {
+        if this == of {
+            return true;
+        }
+
+        let Some(supers) = self.supers.get(this) else {
+            return false;
+        };
+
+        // Do a depth first search to see if this is a direct subtype of if any
+        // supertype is a parent.
+        supers.contains(of)
+            || supers
+                .iter()
+                .any(|parent| self.is_class_subtype(parent, of))
+    }
+
+    /// Like [`Subtypes::supertype_path`], but only looking at the subtyping
+    /// graph of the classes themselves.
+    pub fn class_path(&self, this: &Type, of: &Type) -> Option<Vec<Type>> {
+        if this == of {
+            return Some(vec![*this]);
+        }
+
+        let supers = self.supers.get(this)?;
+        supers
+            .iter()
+            .find_map(|sup| self.class_path(sup, of))
+            .map(|mut path| {
+                path.push(*this);
+                path
+            })
+    }
+
+    /// The type parameters of a class as arguments to itself.
+    fn own_arguments(&self, db: &dyn Db, class: &Name) -> Vec<Type> {
+        self.params(class)
+            .iter()
+            .map(|(param, _)| Type::new(db, TypeNode::Name(*param)))
+            .collect()
+    }
+
+    /// The arguments `parent` gets when `class` is applied to `args`. Parents
+    /// added with just [`Subtypes::add_subtype`] get no arguments.
+    fn parent_arguments(
+        &self,
+        db: &dyn Db,
+        class: Name,
+        args: &[Type],
+        parent: Name,
+    ) -> Option<Vec<Type>> {
+        let inherit = self.inherits.get(&class).and_then(|inherits| {
+            inherits.iter().find(|ty| {
+                matches!(ty.node(db), TypeNode::Name(name) | TypeNode::Applied(name, _) if name == parent)
+            })
+        });
+
+        let Some(inherit) = inherit else {
+            return Some(Vec::new());
+        };
+
+        let params: Vec<_> = self.params(&class).iter().map(|(name, _)| *name).collect();
+        if params.len() != args.len() {
+            return None;
+        }
+
+        match Substitution::new(&params, args).apply(db, inherit).node(db) {
+            TypeNode::Applied(_, args) => Some(args),
+            _ => Some(Vec::new()),
         }
     }
 }
@@ -1,9 +1,11 @@
+use crate::hir;
 use crate::names::{Name, NameNode, NamePart, NamePrefix};
-use crate::source::Source;
-use crate::Db;
+use crate::resolution::resolve_names;
+use crate::source::{prelude, Lints, Project, Source};
+use crate::{Db, Messages};
 
 use super::subtyping::Subtypes;
-use super::{Type, TypeNode};
+use super::{annotate, member_table, pretty_type, type_info, Type, TypeNode};
 
 #[derive(Default)]
 #[salsa::db(crate::Jar)]
@@ -14,24 +16,35 @@ struct Database {
 impl salsa::Database for Database {}
 
 fn make_name(db: &dyn Db, name: impl Into<String>) -> Type {
-    let source = Source::new(db, String::new(), String::new());
+    let source = Source::new(db, String::new(), String::new(), Lints::default());
     let scope = NamePrefix::Source(source);
 
     let name = Name::new(db, scope, NamePart::new(db, NameNode::Type(name.into())));
     Type::new(db, TypeNode::Name(name))
 }
 
+/// Find the class with the given name declared anywhere in `source`.
+fn find_class(db: &dyn Db, source: Source, name: &str) -> Name {
+    let classes = resolve_names(db, source).tree(db).classes(db);
+    classes
+        .keys()
+        .copied()
+        .find(|class| pretty_type(db, &Type::new(db, TypeNode::Name(*class))) == name)
+        .unwrap()
+}
+
 fn test_subtypes(
+    db: &dyn Db,
     subtypes: &Subtypes,
     holds: impl IntoIterator<Item = (Type, Type)>,
     doesnt: impl IntoIterator<Item = (Type, Type)>,
 ) {
     for (a, b) in holds {
-        assert!(subtypes.is_subtype(&a, &b));
+        assert!(subtypes.is_subtype(db, &a, &b));
     }
 
     for (a, b) in doesnt {
-        assert!(!subtypes.is_subtype(&a, &b));
+        assert!(!subtypes.is_subtype(db, &a, &b));
     }
 
     subtypes.assert_integrity();
@@ -47,7 +60,7 @@ fn subtype_reflexivity() {
     let holds = [(a, a)];
     let doesnt = [];
 
-    test_subtypes(&subtypes, holds, doesnt);
+    test_subtypes(&db, &subtypes, holds, doesnt);
 }
 
 #[test]
@@ -67,7 +80,7 @@ fn subtype_transitivity() {
     let holds = [(a, c), (a, d), (b, d)];
     let doesnt = [(d, a), (d, b), (d, c), (c, a), (c, b), (b, a)];
 
-    test_subtypes(&subtypes, holds, doesnt);
+    test_subtypes(&db, &subtypes, holds, doesnt);
 }
 
 #[test]
@@ -97,7 +110,7 @@ fn subtype_direct_subtypes() {
         (d, c),
     ];
 
-    test_subtypes(&subtypes, holds, doesnt);
+    test_subtypes(&db, &subtypes, holds, doesnt);
 }
 
 #[test]
@@ -134,10 +147,10 @@ fn subtype_lattice() {
     subtypes.add_subtype(e, f);
 
     let holds = [a, b, c, d, e, t].into_iter().map(|of| (f, of));
-    test_subtypes(&subtypes, holds, []);
+    test_subtypes(&db, &subtypes, holds, []);
 
     let holds = [a, b, c, d, e, f].into_iter().map(|this| (this, t));
-    test_subtypes(&subtypes, holds, []);
+    test_subtypes(&db, &subtypes, holds, []);
 
     let some_unrelated = [
         (a, b),
@@ -152,7 +165,7 @@ fn subtype_lattice() {
         (e, a),
     ];
 
-    test_subtypes(&subtypes, [], some_unrelated);
+    test_subtypes(&db, &subtypes, [], some_unrelated);
 }
 
 #[test]
@@ -169,3 +182,775 @@ fn subtype_cycle() {
     subtypes.add_subtype(b, c);
     subtypes.add_subtype(c, a);
 }
+
+#[test]
+fn generic_member_substitution() {
+    let db = Database::default();
+    let text = "class Seq[T] end\nclass Thing end\nclass List[A] is Seq(A) end\nclass Things is List(Thing) end";
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let info = type_info(&db, source);
+
+    let class = |name: &str| Type::new(&db, TypeNode::Name(find_class(&db, source, name)));
+
+    let seq = find_class(&db, source, "Seq");
+    let (param, _) = info.subtypes(&db).params(&seq)[0];
+    let param = Type::new(&db, TypeNode::Name(param));
+
+    // A member `(T) T` of `Seq`, seen through a `Things`
+    let member = Type::new(&db, TypeNode::Function(vec![param], param));
+    let thing = class("Thing");
+    let expected = Type::new(&db, TypeNode::Function(vec![thing], thing));
+
+    let actual = info.member_type(&db, &class("Things"), seq, &member);
+    assert_eq!(Some(expected), actual);
+}
+
+#[test]
+fn subtype_variance() {
+    let db = Database::default();
+    let text = "class Animal end\nclass Cat is Animal end\nclass Box[out T] end\nclass Sink[in T] end\nclass Cell[T] end";
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let subtypes = type_info(&db, source).subtypes(&db);
+
+    let class = |name: &str| find_class(&db, source, name);
+
+    let animal = Type::new(&db, TypeNode::Name(class("Animal")));
+    let cat = Type::new(&db, TypeNode::Name(class("Cat")));
+    let apply = |name: &str, arg: Type| Type::new(&db, TypeNode::Applied(class(name), vec![arg]));
+
+    let holds = [
+        (apply("Box", cat), apply("Box", animal)),
+        (apply("Sink", animal), apply("Sink", cat)),
+        (apply("Cell", cat), apply("Cell", cat)),
+    ];
+
+    let doesnt = [
+        (apply("Box", animal), apply("Box", cat)),
+        (apply("Sink", cat), apply("Sink", animal)),
+        (apply("Cell", cat), apply("Cell", animal)),
+        (apply("Cell", animal), apply("Cell", cat)),
+    ];
+
+    test_subtypes(&db, subtypes, holds, doesnt);
+}
+
+#[test]
+fn subtype_variants() {
+    let db = Database::default();
+    let text =
+        "variant Part\nvariant Ident\nclass Value end\nclass Type end\nend\nclass Invalid end\nend";
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let info = type_info(&db, source);
+
+    let class = |name: &str| Type::new(&db, TypeNode::Name(find_class(&db, source, name)));
+    let part = class("Part");
+    let ident = class("Ident");
+    let value = class("Value");
+    let invalid = class("Invalid");
+
+    let holds = [
+        (invalid, part),
+        (ident, part),
+        (value, ident),
+        (value, part),
+    ];
+    let doesnt = [
+        (part, invalid),
+        (part, ident),
+        (value, invalid),
+        (invalid, ident),
+    ];
+    test_subtypes(&db, info.subtypes(&db), holds, doesnt);
+
+    assert!(!info.open(&db).contains(&part));
+    assert!(!info.open(&db).contains(&ident));
+    assert!(info.open(&db).contains(&invalid));
+}
+
+#[test]
+fn check_variance() {
+    let db = Database::default();
+    let text = "\
+class Source[out T]
+    function get(this &) T
+    function put(this &, value T) Unit
+end
+
+class Sink[in T]
+    function put(this &, value T) Unit
+    function get(this &) T
+end
+
+variant Option[out T]
+    class None end
+
+    class Some
+        var value T
+    end
+
+    class Lazy
+        function get(this &) T
+    end
+end
+";
+
+    let (codes, _) = check(&db, text);
+    let mut codes: Vec<_> = codes.iter().map(String::as_str).collect();
+    codes.sort();
+
+    // One for each class, including the mutable field of the case
+    assert_eq!(codes, ["ET04", "ET04", "ET04"]);
+}
+
+/// Type check the text, returning the codes of every reported message along
+/// with the checked items.
+fn check(db: &dyn Db, text: &str) -> (Vec<String>, hir::Items) {
+    let source = Source::new(db, text.into(), String::new(), Lints::NONE);
+    let items = annotate(db, source);
+    let codes = annotate::accumulated::<Messages>(db, source)
+        .into_iter()
+        .filter_map(|message| message.code)
+        .collect();
+
+    (codes, items)
+}
+
+#[test]
+fn check_generic_calls() {
+    let db = Database::default();
+    let text = "\
+variant List[T]
+    class Nil end
+    class Cons
+        var head T
+        var tail List(T)
+    end
+end
+
+function id[T](x T) T
+    return x
+end
+
+function apply(f (Nat) Nat, x Nat) Nat
+    return f(x)
+end
+
+function main() Unit
+    let xs List(Nat) := List.Cons(1, List.Cons(2, List.Nil()))
+    let n Nat := id(5)
+    let m Nat := apply(function(x) return x * 2 end, n)
+end
+";
+
+    let (codes, items) = check(&db, text);
+    assert!(codes.is_empty(), "unexpected messages {codes:?}");
+
+    let values = items.values(&db);
+    let main = values
+        .iter()
+        .find(|value| pretty_type(&db, &value.anno) == "() Unit")
+        .unwrap();
+
+    let hir::ValueNode::Function {
+        body: Some(body), ..
+    } = &main.node
+    else {
+        panic!("main has a body");
+    };
+
+    let hir::StatementNode::Assignment(_, call) = &body.statements[1].node else {
+        panic!("second statement is a let");
+    };
+
+    assert_eq!(TypeNode::Nat, call.anno.node(&db));
+}
+
+#[test]
+fn check_errors() {
+    let db = Database::default();
+    let text = "\
+class Point
+    var x Int
+    var y Int
+
+    function origin() Point
+        return Point(0)
+    end
+end
+
+function main() Unit
+    let p Point := Point.origin()
+    let b Boolean := p.x
+    p.z := 5
+    main := main
+    p - p
+    this
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(
+        codes,
+        ["ET10", "ET13", "ET16", "ET14", "ET17", "ET12"].map(String::from)
+    );
+}
+
+#[test]
+fn check_number_literal_ranges() {
+    let db = Database::default();
+    let text = "\
+function main() Unit
+    let a Int := 9223372036854775807
+    let b Int := -9223372036854775808
+    let c Nat := 18446744073709551615
+    let d Nat := 0x8000_0000_0000_0000
+
+    let e Int := 9223372036854775808
+    let f Int := -9223372036854775809
+    let g Nat := 18446744073709551616
+    let h Nat := 1.5
+    let i Int := 1nat
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(
+        codes,
+        ["ET01", "ET01", "ET01", "ET02", "ET10"].map(String::from)
+    );
+}
+
+#[test]
+fn member_inheritance() {
+    let db = Database::default();
+    let text = "\
+class Animal
+    function name(this &) Int
+        return 0
+    end
+
+    function legs(this &) Int
+        return 4
+    end
+private
+    var secret Int
+end
+
+class Bird is Animal
+    function legs(this &) Int
+        return 2
+    end
+end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let table = member_table(&db, source);
+
+    let animal = find_class(&db, source, "Animal");
+    let bird = find_class(&db, source, "Bird");
+    let part = |name: &str| NamePart::new(&db, NameNode::Value(name.into()));
+
+    let name = table.lookup(&db, bird, part("name")).unwrap();
+    assert_eq!(animal, name.owner);
+    assert!(name.public);
+
+    let legs = table.lookup(&db, bird, part("legs")).unwrap();
+    assert_eq!(bird, legs.owner);
+    assert_ne!(table.lookup(&db, animal, part("legs")), Some(legs));
+
+    let secret = table.lookup(&db, bird, part("secret")).unwrap();
+    assert_eq!(animal, secret.owner);
+    assert!(!secret.public);
+
+    assert_eq!(None, table.lookup(&db, animal, part("missing")));
+}
+
+#[test]
+fn check_overrides() {
+    let db = Database::default();
+    let text = "\
+class Shape
+    function area(this &) Int
+        return 0
+    end
+
+    function sides(this) Int
+        return 0
+    end
+end
+
+class Other end
+
+class Square is Shape
+    function Shape.area(this &) Int
+        return 4
+    end
+
+    function Shape.sides(this &) Int
+        return 4
+    end
+
+    function Shape.corners(this) Int
+        return 4
+    end
+end
+
+class Circle is Shape
+    function Shape.area(this &) Boolean
+        return 1 = 1
+    end
+end
+
+class Triangle is Shape
+    function Other.area(this &) Int
+        return 3
+    end
+end
+
+function Shape.area() Int
+    return 0
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(
+        codes,
+        ["ET20", "ET24", "ET22", "ET23", "ET21"].map(String::from)
+    );
+}
+
+#[test]
+fn check_implicit_overrides() {
+    let db = Database::default();
+    let text = "\
+class Shape
+    function length(this) Int
+        return 0
+    end
+
+    function width(this &) Int
+        return 0
+    end
+
+    private function secret(this) Int
+        return 0
+    end
+end
+
+class Line is Shape
+    function length(this &) Nat
+        return 1
+    end
+
+    function width(this &) Boolean
+        return 1 = 1
+    end
+
+    function secret(this &) Boolean
+        return 1 = 1
+    end
+end
+";
+
+    // `secret` is private to Shape, so it is hidden rather than overridden
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ET24", "ET23"].map(String::from));
+}
+
+#[test]
+fn check_abstract_members() {
+    let db = Database::default();
+    let text = "\
+class Shape
+    function area(this &) Int
+end
+
+class Square is Shape
+    function area(this &) Int
+        return 4
+    end
+end
+
+class Blob is Shape
+    function color(this) Int
+        return 0
+    end
+end
+
+function main() Unit
+    let shape Shape := Shape()
+    let square Shape := Square()
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ET26", "ET25"].map(String::from));
+}
+
+#[test]
+fn check_case_exhaustiveness() {
+    let db = Database::default();
+    let text = "\
+class String end
+
+variant Part
+    variant Ident
+        var name String
+
+        class Value end
+        class Type end
+    end
+
+    class Invalid end
+end
+
+function covered(p Part) Int
+    case p
+        is Part.Ident(name)
+            return 1
+        end
+        is Part.Invalid
+            return 2
+        end
+    end
+
+    return 0
+end
+
+function missing(p Part) Int
+    case p
+        is Part.Ident.Value(name)
+            return 1
+        end
+    end
+
+    return 0
+end
+
+function redundant(p Part) Int
+    case p
+        is Part.Ident.Value(name)
+            return 1
+        end
+        is other
+            return 2
+        end
+        is Part.Invalid
+            return 3
+        end
+    end
+
+    return 0
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ET30", "WT30"].map(String::from));
+}
+
+#[test]
+fn check_closed_variants() {
+    let db = Database::default();
+    let text = "\
+variant Shape
+    class Circle end
+
+    variant Polygon
+        class Square end
+    end
+end
+
+class Triangle is Shape end
+
+function sides(s Shape) Int
+    case s
+        is Shape.Circle
+            return 0
+        end
+        is Shape.Polygon
+            return 4
+        end
+    end
+
+    return 0
+end
+";
+
+    // Triangle is not a case of Shape, so the case above still covers it
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ET07"].map(String::from));
+}
+
+#[test]
+fn linearize_supertypes() {
+    let db = Database::default();
+    let text = "\
+class A
+    function f(this) Int
+end
+
+class B is A
+    function f(this) Int
+        return 1
+    end
+end
+
+class C is A
+    function f(this) Int
+        return 2
+    end
+end
+
+class D is B, C end
+
+class E is B, C
+    function A.f(this) Int
+        return 3
+    end
+end
+
+class F is A, B end
+";
+
+    let source = Source::new(&db, text.into(), String::new(), Lints::default());
+    let table = member_table(&db, source);
+    let class = |name| find_class(&db, source, name);
+
+    assert_eq!(
+        &vec![class("D"), class("B"), class("C"), class("A")],
+        &table.linearizations(&db)[&class("D")]
+    );
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ET06", "ET05"].map(String::from));
+}
+
+#[test]
+fn check_private_members() {
+    let db = Database::default();
+    let text = "\
+class Account
+    function balance(this &) Int
+        return this.cents
+    end
+
+    class Audit
+        function check(account &Account) Int
+            return account.cents
+        end
+    end
+private
+    var cents Int
+
+    class Ledger end
+end
+
+class Savings is Account
+    function peek(this &) Int
+        return this.cents
+    end
+end
+
+function main(account Account) Int
+    let ledger Account.Ledger := Account.Ledger()
+    return account.cents + account.balance()
+end
+";
+
+    let (codes, _) = check(&db, text);
+    assert_eq!(codes, ["ER05"; 4].map(String::from));
+}
+
+#[test]
+fn check_with_prelude() {
+    let db = Database::default();
+    let text = "\
+variant NamePart is Stringable
+    variant Identifier
+        var name String
+
+        class Value end
+        class Type end
+    end
+
+    class Invalid end
+
+    function to_string(this &) String
+        case this
+            is Invalid
+                return \"<invalid>\"
+            end
+
+            is Identifier.Value(name)
+                return \"var \" + name
+            end
+
+            is Identifier.Type(name)
+                return \"type \" + name
+            end
+        end
+    end
+end
+
+function main(part NamePart) Boolean
+    let names List(String) := List.Cons(part.to_string(), List.Nil())
+    if names.length() = 1
+        return part.to_string() = \"var x\"
+    end
+
+    return names.length() = 0
+end
+";
+
+    let source = Source::new(&db, text.into(), "main.rry".into(), Lints::NONE);
+    let prelude = prelude(&db);
+    Project::new(&db, vec![source], prelude);
+
+    for source in [prelude, source] {
+        annotate(&db, source);
+        let messages = annotate::accumulated::<Messages>(&db, source);
+        assert!(messages.is_empty(), "{messages:#?}");
+    }
+}
+
+#[test]
+fn check_runtime_members() {
+    let db = Database::default();
+    let text = "\
+class Text is String end
+
+class Shown is Stringable end
+";
+
+    let source = Source::new(&db, text.into(), "main.rry".into(), Lints::NONE);
+    Project::new(&db, vec![source], prelude(&db));
+
+    annotate(&db, source);
+    let codes: Vec<_> = annotate::accumulated::<Messages>(&db, source)
+        .into_iter()
+        .filter_map(|message| message.code)
+        .collect();
+
+    // The members of `String` are provided by the runtime, but `Stringable`
+    // leaves `to_string` abstract
+    assert_eq!(codes, ["ET25"]);
+}
+
+#[test]
+fn check_imported_classes_once() {
+    let db = Database::default();
+    let source = |name: &str, text: &str| Source::new(&db, text.into(), name.into(), Lints::NONE);
+
+    let shapes = source(
+        "shapes.rry",
+        "\
+class Box[out T]
+    var value T
+end
+
+class Tagged is Box
+    var tag Box
+end
+
+class Left
+    function name(this &) Int
+end
+
+class Right
+    function name(this &) Int
+end
+
+class Both is Left, Right end
+",
+    );
+
+    let main = source(
+        "main.rry",
+        "\
+import shapes
+
+function main(both Both) Int
+    return both.name()
+end
+",
+    );
+
+    Project::new(&db, vec![main, shapes], prelude(&db));
+
+    // Every source is checked in turn, and errors in `shapes` are only
+    // reported along with it
+    let mut codes: Vec<_> = [main, shapes]
+        .into_iter()
+        .flat_map(|source| {
+            annotate(&db, source);
+            annotate::accumulated::<Messages>(&db, source)
+        })
+        .filter(|message| message.labels[0].at.source == shapes)
+        .filter_map(|message| message.code)
+        .collect();
+    codes.sort();
+
+    assert_eq!(
+        codes,
+        ["ET03", "ET03", "ET04", "ET06", "ET25"].map(String::from)
+    );
+}
+
+#[test]
+fn check_interpolation() {
+    let db = Database::default();
+    let text = "\
+class Name is Stringable
+    function to_string(this &) String
+        return \"name\"
+    end
+end
+
+function greet(name Name, title String, count Int) String
+    return \"{name&}, {title} and {count}\"
+end
+";
+
+    let source = Source::new(&db, text.into(), "main.rry".into(), Lints::NONE);
+    Project::new(&db, vec![source], prelude(&db));
+
+    let items = annotate(&db, source);
+    let codes: Vec<_> = annotate::accumulated::<Messages>(&db, source)
+        .into_iter()
+        .filter_map(|message| message.code)
+        .collect();
+
+    // `Int` isn't `Stringable`, but builtin types can still be interpolated
+    assert!(codes.is_empty());
+
+    let hir::ValueNode::Function {
+        body: Some(body), ..
+    } = &items.values(&db)[0].node
+    else {
+        panic!("greet has a body");
+    };
+
+    let hir::StatementNode::Return(string) = &body.statements[0].node else {
+        panic!("greet returns a string");
+    };
+
+    let hir::ExpressionNode::Interpolated(parts) = &string.node else {
+        panic!("greet returns an interpolated string");
+    };
+
+    let calls: Vec<_> = parts
+        .iter()
+        .filter_map(|part| match part {
+            hir::StringPart::Expression(expr) => Some(expr),
+            hir::StringPart::Text(_) => None,
+        })
+        .map(|expr| match expr.node {
+            hir::ExpressionNode::Call(..) => "to_string",
+            hir::ExpressionNode::ToString(..) => "builtin",
+            _ => "unchanged",
+        })
+        .collect();
+
+    // Classes are converted with `to_string`, and builtin types by the runtime
+    assert_eq!(calls, ["to_string", "to_string", "builtin"]);
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use crate::ast::Variance;
+use crate::messages::MessageMaker;
+use crate::names::Name;
+use crate::rst::{Class, Type, TypeNode, ValueNode};
+use crate::source::Span;
+use crate::Db;
+
+/// Checks that the type parameters of a class are only used in positions their
+/// variance allows. A covariant parameter may only be produced (e.g. returned
+/// from a method) and a contravariant one may only be consumed (e.g. taken as an
+/// argument), while invariant parameters may be used anywhere.
+pub struct VarianceChecker<'a> {
+    db: &'a dyn Db,
+    classes: &'a HashMap<Name, Class>,
+
+    /// The parameters of the class being checked which have a variance, and
+    /// where they were declared.
+    params: HashMap<Name, (Variance, Span)>,
+}
+
+impl<'a> VarianceChecker<'a> {
+    /// Check a class, or a case of `variant` if the class is nested in one.
+    /// Cases are written in terms of the type parameters of their variant, so
+    /// those are checked along with the parameters of the class itself.
+    pub fn check_class(
+        db: &'a dyn Db,
+        classes: &'a HashMap<Name, Class>,
+        class: &Class,
+        variant: Option<&Class>,
+    ) {
+        let inherited = variant.map(|variant| variant.type_params.as_slice());
+        let params: HashMap<_, _> = class
+            .type_params
+            .iter()
+            .chain(inherited.unwrap_or_default())
+            .filter(|param| param.variance != Variance::Invariant)
+            .map(|param| (param.name, (param.variance, param.span)))
+            .collect();
+
+        if params.is_empty() {
+            return;
+        }
+
+        let checker = Self {
+            db,
+            classes,
+            params,
+        };
+
+        // A subtype can be used wherever its supertypes are expected
+        for inherit in class.inherits.iter() {
+            checker.check(inherit, Variance::Covariant);
+        }
+
+        for value in class.fields.values.iter() {
+            match &value.node {
+                ValueNode::Function {
+                    args, return_type, ..
+                } => {
+                    for (_, ty) in args {
+                        checker.check(ty, Variance::Contravariant);
+                    }
+
+                    checker.check(return_type, Variance::Covariant);
+                }
+
+                // Fields are mutable, so they are both read and written
+                ValueNode::Variable { anno, .. } => checker.check(anno, Variance::Invariant),
+            }
+        }
+    }
+
+    /// Check the type `ty` occuring in a position of the given variance.
+    fn check(&self, ty: &Type, position: Variance) {
+        match &ty.node {
+            TypeNode::Name(name) => {
+                let Some((variance, declared)) = self.params.get(name) else {
+                    return;
+                };
+
+                if position != *variance {
+                    MessageMaker::at(self.db, ty.span)
+                        .types_variance_mismatch(*declared, *variance, position);
+                }
+            }
+
+            TypeNode::Applied(to, args) => {
+                let params = match &to.node {
+                    TypeNode::Name(name) => self.classes.get(name),
+                    _ => None,
+                }
+                .map(|class| class.type_params.as_slice())
+                .unwrap_or(&[]);
+
+                for (index, arg) in args.iter().enumerate() {
+                    let variance = params
+                        .get(index)
+                        .map(|param| param.variance)
+                        .unwrap_or(Variance::Invariant);
+
+                    self.check(arg, compose(position, variance));
+                }
+            }
+
+            TypeNode::Function(args, to) => {
+                for arg in args {
+                    self.check(arg, compose(position, Variance::Contravariant));
+                }
+
+                self.check(to, position);
+            }
+
+            // References may be written through
+            TypeNode::Reference(of) => self.check(of, Variance::Invariant),
+
+            TypeNode::Field(..)
+            | TypeNode::Int
+            | TypeNode::Nat
+            | TypeNode::Boolean
+            | TypeNode::Unit
+            | TypeNode::Invalid => {}
+        }
+    }
+}
+
+/// The variance of a position nested at `inner` within a position `outer`.
+fn compose(outer: Variance, inner: Variance) -> Variance {
+    match (outer, inner) {
+        (Variance::Invariant, _) | (_, Variance::Invariant) => Variance::Invariant,
+        (Variance::Covariant, inner) => inner,
+        (Variance::Contravariant, Variance::Covariant) => Variance::Contravariant,
+        (Variance::Contravariant, Variance::Contravariant) => Variance::Covariant,
+    }
+}